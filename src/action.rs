@@ -1,13 +1,51 @@
 use crate::state::board::Block;
+use crate::state::calendar::GameSpeed;
+use crate::state::menu::building::{Building, BuyBuildingButton, Category};
 use crate::state::menu::sidebar::SidebarMenu;
 
+#[derive(PartialEq, Clone, Copy)]
 pub enum GameAction {
     Hover { block: Option<Block> },
     SidebarHover { button: Option<SidebarMenu> },
     OpenMenu { menu: SidebarMenu },
+    BuyBuildingHover { button: Option<BuyBuildingButton> },
+    SelectBuildingCategory { category: Category },
+    SelectBuilding { building: Building },
+    BuyBuilding,
+    CloseBuyBuildingScreen,
     PlaceStructure,
+    ConfirmFlatten,
+    Demolish,
     Focus,
     RaiseTerrain,
+    SelectTerraform,
+    SelectFlatten,
+    Flatten,
+    CancelFlatten,
+    AdjustFlattenLevel { delta: i8 },
     RotateStructure,
     LowerTerrain,
+    ResizeBrush { delta: i8 },
+    CycleBrushShape,
+    ToggleBrushFalloff,
+    ToggleBulldoze,
+    ExportHeightmap,
+    ExportMap,
+    SaveGame,
+    LoadGame,
+    OpenLoadScreen,
+    SelectSlot { delta: i8 },
+    NameSlot,
+    TypeSlotName { character: char },
+    EraseSlotName,
+    CancelSlotName,
+    CloseLoadScreen,
+    CloseFinancesScreen,
+    Borrow,
+    RepayLoan,
+    Undo,
+    Redo,
+    TogglePause,
+    SetSpeed { speed: GameSpeed },
+    Quit,
 }
@@ -0,0 +1,177 @@
+use crate::action::GameAction;
+use crate::state::game::GameState;
+use crate::storage::recording::Recording;
+use crate::storage::save::SaveError;
+use crate::systems;
+use crate::systems::clock;
+use crate::systems::replay::Replay;
+
+/// Runs the game without a window, one tick at a time, so it can be driven by tools and tests.
+pub struct Simulation {
+    pub game: GameState,
+    tick: u64,
+    script: Option<Replay>,
+}
+
+impl Simulation {
+    pub fn new(game: GameState) -> Self {
+        Self {
+            game,
+            tick: 0,
+            script: None,
+        }
+    }
+
+    /// Feeds in a replay's actions as the ticks pass.
+    pub fn scripted(game: GameState, script: Replay) -> Self {
+        Self {
+            game,
+            tick: 0,
+            script: Some(script),
+        }
+    }
+
+    /// Starts from the game a recording began with and plays it as the script.
+    pub fn from_recording(recording: Recording) -> Result<Self, SaveError> {
+        let (game, script) = Replay::start(recording)?;
+        Ok(Self::scripted(game, script))
+    }
+
+    /// The number of ticks run so far.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// The number of ticks needed to play the whole script, or 0 without one.
+    pub fn script_length(&self) -> u64 {
+        self.script
+            .as_ref()
+            .map_or(0, |script| script.last_tick() + 1)
+    }
+
+    /// Applies an action as though the player had taken it during the current tick.
+    pub fn apply_action(&mut self, action: GameAction) {
+        systems::apply_action(&mut self.game, action);
+    }
+
+    /// Applies whatever the script has for the current tick, runs the simulation for it at the
+    /// game's speed, then moves on to the next tick.
+    pub fn step(&mut self) {
+        if let Some(script) = self.script.as_mut() {
+            script.apply_tick(&mut self.game, self.tick);
+            if script.is_finished() {
+                self.script = None;
+            }
+        }
+        clock::apply_update(&mut self.game);
+        self.tick += 1;
+    }
+
+    pub fn run(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Block, Board};
+    use crate::state::calendar::{GameSpeed, START_DATE, TICKS_PER_DAY};
+    use crate::state::company::STARTING_CASH;
+    use crate::storage::recording::{decode_recording, Recorder};
+    use crate::storage::{save, text_map};
+    use crate::systems::generation::{generate_terrain, TerrainParams};
+    use std::env;
+    use std::fs;
+
+    fn new_game() -> GameState {
+        let mut board = Board::new(64, 64);
+        generate_terrain(&mut board, &TerrainParams::new(5));
+        GameState::new(board)
+    }
+
+    #[test]
+    fn scripts_play_out_at_their_ticks() {
+        let game = new_game();
+        let mut recorder = Recorder::new(Vec::new(), &game).unwrap();
+        let hover = GameAction::Hover {
+            block: Some(Block { x: 20, y: 20 }),
+        };
+        recorder.record_action(2, hover).unwrap();
+        recorder
+            .record_action(5, GameAction::PlaceStructure)
+            .unwrap();
+        let recording = decode_recording(&recorder.into_inner()).unwrap();
+
+        let mut simulation = Simulation::from_recording(recording).unwrap();
+        assert_eq!(simulation.script_length(), 6);
+        simulation.run(5);
+        assert_eq!(simulation.game.board.structures().count(), 0);
+        simulation.run(1);
+        assert_eq!(simulation.game.board.structures().count(), 1);
+        assert_eq!(simulation.tick(), 6);
+
+        let mut unscripted = Simulation::new(game);
+        unscripted.apply_action(hover);
+        unscripted.apply_action(GameAction::PlaceStructure);
+        unscripted.run(6);
+        assert!(save::encode_game(&simulation.game) == save::encode_game(&unscripted.game));
+    }
+
+    /// Drives a simulation the way `--headless --replay --ticks --dump --dump-map` does, and
+    /// checks what it writes out.
+    #[test]
+    fn recordings_run_headless_and_dump_the_result() {
+        let game = new_game();
+        let mut recorder = Recorder::new(Vec::new(), &game).unwrap();
+        let script = [
+            (
+                1,
+                GameAction::SetSpeed {
+                    speed: GameSpeed::X8,
+                },
+            ),
+            (
+                2,
+                GameAction::Hover {
+                    block: Some(Block { x: 20, y: 20 }),
+                },
+            ),
+            (3, GameAction::PlaceStructure),
+        ];
+        for &(tick, action) in script.iter() {
+            recorder.record_action(tick, action).unwrap();
+        }
+        let recording = decode_recording(&recorder.into_inner()).unwrap();
+
+        let mut simulation = Simulation::from_recording(recording).unwrap();
+        let ticks = (TICKS_PER_DAY * 2) as u64;
+        simulation.run(ticks);
+        assert_eq!(simulation.tick(), ticks);
+
+        let game = &simulation.game;
+        assert!(game.speed == GameSpeed::X8);
+        assert!(game.date > START_DATE);
+        let (_, placement) = game.board.structures().next().unwrap();
+        assert_eq!(placement.origin(), Block { x: 20, y: 20 });
+        assert_eq!(
+            game.company.cash,
+            STARTING_CASH - placement.structure().cost()
+        );
+
+        let dir = env::temp_dir();
+        let save_path = dir.join(format!("titan-headless-{}.sav", std::process::id()));
+        let map_path = dir.join(format!("titan-headless-{}.txt", std::process::id()));
+        save::save_game(game, &save_path).unwrap();
+        text_map::save_map(&game.board, &map_path).unwrap();
+        let saved = save::load_game(&save_path).unwrap();
+        let map = text_map::load_map(&map_path).unwrap();
+        fs::remove_file(&save_path).unwrap();
+        fs::remove_file(&map_path).unwrap();
+
+        assert!(save::encode_game(&saved) == save::encode_game(game));
+        assert_eq!(text_map::write_map(&map), text_map::write_map(&game.board));
+    }
+}
@@ -0,0 +1,10 @@
+#[cfg(feature = "sdl")]
+extern crate sdl2;
+
+pub mod action;
+pub mod headless;
+pub mod state;
+pub mod storage;
+pub mod systems;
+#[cfg(feature = "sdl")]
+pub mod view;
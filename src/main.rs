@@ -1,145 +1,201 @@
-extern crate sdl2;
-
-mod action;
-mod state;
-mod systems;
-mod view;
-
-use action::GameAction;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::mouse::MouseButton;
-use state::game::GameState;
-use std::ops::{Add, Sub};
-use std::thread;
-use std::time::{Duration, Instant};
-use view::{Interface, KeyboardKey, PlayerInteraction, ScreenState};
-
-const UPDATES_PER_SECOND: u32 = 120;
-const MAX_FRAMES_PER_SECOND: u32 = 60;
+use std::env;
+#[cfg(feature = "sdl")]
+use std::time::Duration;
+use std::time::{SystemTime, UNIX_EPOCH};
+use titan::headless::Simulation;
+use titan::state;
+use titan::state::board::{Board, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use titan::state::game::{GameState, MAX_SAVE_NAME_LENGTH};
+use titan::storage;
+#[cfg(feature = "sdl")]
+use titan::storage::recording::Recorder;
+#[cfg(feature = "sdl")]
+use titan::storage::slots::AutosaveSettings;
+use titan::systems;
+use titan::systems::generation::TerrainParams;
+use titan::systems::replay::Replay;
 
 fn main() -> Result<(), String> {
-    let sdl_ctx = sdl2::init()?;
-    let vid_subsystem = sdl_ctx.video()?;
-
-    let window = vid_subsystem
-        .window("Titan", 640, 480)
-        .position_centered()
-        .allow_highdpi()
-        .fullscreen_desktop()
-        .opengl()
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let (window_width, window_height) = window.size();
-    let (drawable_x, drawable_y) = window.drawable_size();
-
-    let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
-    let texture_creator = canvas.texture_creator();
-
-    let screen = ScreenState::new(window_width, window_height, drawable_x, drawable_y);
-    let interface = Interface::new(texture_creator, screen);
-
-    let mut game = GameState::new();
-
-    let mut event_pump = sdl_ctx.event_pump()?;
-    let update_interval = Duration::new(0, 1_000_000_000 / UPDATES_PER_SECOND);
-    let mut next_update = Instant::now();
-    let mut last_frame = Instant::now();
-    let mut frame_count: u64 = 0;
-    'running: loop {
-        let mut player_interactions = Vec::new();
-
-        for event in event_pump.poll_iter() {
-            match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => break 'running,
-                Event::KeyDown {
-                    keycode: Some(keycode),
-                    ..
-                } => {
-                    let key = match keycode {
-                        Keycode::Space => Some(KeyboardKey::Space),
-                        _ => None,
-                    };
-                    if let Some(key) = key {
-                        player_interactions.push(PlayerInteraction::KeyPress { key });
-                    }
-                }
-                Event::MouseMotion { x, y, .. } => {
-                    let x = screen.scale_x(x) as i32;
-                    let y = screen.scale_y(y) as i32;
-                    let player_action = PlayerInteraction::CursorMove { x, y };
-                    player_interactions.push(player_action);
-                }
-                Event::MouseButtonDown {
-                    x, y, mouse_btn, ..
-                } => {
-                    let x = screen.scale_x(x) as i32;
-                    let y = screen.scale_y(y) as i32;
-                    let player_action = match mouse_btn {
-                        MouseButton::Left => Some(PlayerInteraction::WindowLeftClick { x, y }),
-                        MouseButton::Right => Some(PlayerInteraction::WindowRightClick { x, y }),
-                        _ => None,
-                    };
-                    if let Some(player_action) = player_action {
-                        player_interactions.push(player_action);
-                    }
-                }
-                _ => {}
-            }
-        }
+    if env::args().any(|arg| arg == "--headless") {
+        return run_headless();
+    }
 
-        // Resolve all actions.
-        for &player_interaction in player_interactions.iter() {
-            match interface.map_player_interaction(&game, player_interaction) {
-                Some(GameAction::Hover { block }) => {
-                    systems::navigation::apply_hover(&mut game, block)
-                }
-                Some(GameAction::Focus) => {
-                    systems::navigation::apply_focus(&mut game);
-                }
-                Some(GameAction::LowerTerrain) => {
-                    systems::terrain::apply_lower_terrain(&mut game);
-                }
-                Some(GameAction::OpenMenu { menu }) => {
-                    systems::menu::apply_open_menu(&mut game, menu);
-                }
-                Some(GameAction::PlaceStructure) => {
-                    systems::structure::apply_place_structure(&mut game);
-                }
-                Some(GameAction::RaiseTerrain) => {
-                    systems::terrain::apply_raise_terrain(&mut game);
-                }
-                Some(GameAction::RotateStructure) => {
-                    systems::structure::apply_rotate_structure(&mut game);
-                }
-                Some(GameAction::SidebarHover { button }) => {
-                    systems::navigation::apply_sidebar_hover(&mut game, button);
-                }
-                None => {}
-            }
-        }
+    run_windowed()
+}
+
+#[cfg(feature = "sdl")]
+fn run_windowed() -> Result<(), String> {
+    let (game, replay) = initial_game()?;
+    let recorder = match arg_value("--record") {
+        Some(path) => Some(
+            Recorder::create(&path, &game)
+                .map_err(|e| format!("Could not record to {}: {}", path, e))?,
+        ),
+        None => None,
+    };
+
+    titan::view::window::run_window(game, replay, recorder, autosave_settings())
+}
+
+#[cfg(not(feature = "sdl"))]
+fn run_windowed() -> Result<(), String> {
+    Err("Built without the sdl feature, so only --headless is available.".to_string())
+}
+
+/// Runs the simulation for `--ticks <n>` ticks without opening a window, playing the recording
+/// given by `--replay` as a script, then writes the result to `--dump <path>` as a save and to
+/// `--dump-map <path>` as a text map.
+fn run_headless() -> Result<(), String> {
+    let (game, replay) = initial_game()?;
+    let mut simulation = match replay {
+        Some(script) => Simulation::scripted(game, script),
+        None => Simulation::new(game),
+    };
+
+    let ticks = match arg_value("--ticks") {
+        Some(ticks) => ticks
+            .parse()
+            .map_err(|_| format!("Invalid tick count: {}", ticks))?,
+        None => simulation.script_length(),
+    };
+    simulation.run(ticks);
+    println!(
+        "Ran {} ticks: {} structures, {} cash, {} owed, {}.",
+        simulation.tick(),
+        simulation.game.board.structures().count(),
+        state::company::format_money(simulation.game.company.cash),
+        state::company::format_money(simulation.game.company.loan),
+        simulation.game.date,
+    );
+    if simulation.game.company.bankrupt {
+        println!("The company has gone bankrupt.");
+    }
 
-        next_update = next_update.add(update_interval);
+    if let Some(path) = arg_value("--dump") {
+        storage::save::save_game(&simulation.game, &path)
+            .map_err(|e| format!("Could not save {}: {}", path, e))?;
+        println!("Game saved to {}.", path);
+    }
+    if let Some(path) = arg_value("--dump-map") {
+        storage::text_map::save_map(&simulation.game.board, &path)
+            .map_err(|e| format!("Could not save {}: {}", path, e))?;
+        println!("Map saved to {}.", path);
+    }
+    Ok(())
+}
+
+/// Starts from the recording given by `--replay`, the save given by `--load` or a new board. A
+/// replay plays out exactly as recorded, so the options which change the game only apply to the
+/// others.
+fn initial_game() -> Result<(GameState, Option<Replay>), String> {
+    if let Some(path) = arg_value("--replay") {
+        let recording = storage::recording::load_recording(&path)
+            .map_err(|e| format!("Could not load {}: {}", path, e))?;
+        let (game, replay) = Replay::start(recording).map_err(|e| e.to_string())?;
+        return Ok((game, Some(replay)));
+    }
+    if let Some(path) = arg_value("--load") {
+        let mut game = storage::save::load_game(&path)
+            .map_err(|e| format!("Could not load {}: {}", path, e))?;
+        configure_game(&mut game)?;
+        return Ok((game, None));
+    }
+    let mut game = GameState::new(new_board()?);
+    configure_game(&mut game)?;
+    Ok((game, None))
+}
 
-        if last_frame.elapsed() > Duration::new(0, 1_000_000_000 / MAX_FRAMES_PER_SECOND) {
-            let render_start = Instant::now();
-            interface.render(&mut canvas, &game)?;
-            frame_count += 1;
-            // println!("Frame {}: {:?}", frame_count, render_start.elapsed());
-            last_frame = Instant::now();
+/// Names the save slot with `--name <name>`, which is held to the length of a name typed into the
+/// load screen, and sets the percentage of a structure's cost paid
+/// back on demolition with `--demolition-refund <percent>`.
+fn configure_game(game: &mut GameState) -> Result<(), String> {
+    if let Some(name) = arg_value("--name") {
+        if name.trim().is_empty() || name.chars().count() > MAX_SAVE_NAME_LENGTH {
+            return Err(format!(
+                "Invalid name: {} (names are 1 to {} characters)",
+                name, MAX_SAVE_NAME_LENGTH
+            ));
         }
+        game.save_name = name.trim().to_string();
+    }
+    if let Some(refund) = arg_value("--demolition-refund") {
+        game.demolition_refund = refund
+            .parse()
+            .ok()
+            .filter(|&percent| percent <= 100)
+            .ok_or_else(|| format!("Invalid demolition refund: {}", refund))?;
+    }
+    Ok(())
+}
+
+/// Uses the seed given by `--seed <n>` so a map can be shared or a bug reproduced, otherwise picks
+/// a fresh one.
+fn map_seed() -> u64 {
+    if let Some(seed) = arg_value("--seed").and_then(|seed| seed.parse().ok()) {
+        return seed;
+    }
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Reads the board size from `--size <w>x<h>` or `--size <n>` for a square board.
+fn board_size() -> (u32, u32) {
+    let size = arg_value("--size").unwrap_or_default();
+    let mut dimensions = size.split('x').map(|d| d.parse::<u32>().ok());
+    let (width, height) = match (dimensions.next(), dimensions.next()) {
+        (Some(Some(w)), Some(Some(h))) => (w, h),
+        (Some(Some(n)), None) => (n, n),
+        _ => (DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE),
+    };
+
+    (
+        width.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+        height.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+    )
+}
 
-        let now = Instant::now();
-        if next_update > now {
-            let delay = next_update.sub(now);
-            thread::sleep(delay);
+fn new_board() -> Result<Board, String> {
+    #[cfg(feature = "sdl")]
+    {
+        if let Some(path) = arg_value("--heightmap") {
+            return storage::heightmap::load_heightmap(&path);
         }
     }
+    if let Some(path) = arg_value("--map") {
+        return storage::text_map::load_map(&path).map_err(|e| format!("{}: {}", path, e));
+    }
 
-    Ok(())
+    let seed = map_seed();
+    println!("Map seed: {}", seed);
+    let (board_width, board_height) = board_size();
+    let mut board = Board::new(board_width, board_height);
+    systems::generation::generate_terrain(&mut board, &TerrainParams::new(seed));
+    Ok(board)
+}
+
+/// Autosaves every `--autosave-minutes <n>` minutes, or never when n is 0, keeping the last
+/// `--autosaves <n>` of them.
+#[cfg(feature = "sdl")]
+fn autosave_settings() -> AutosaveSettings {
+    let mut settings = AutosaveSettings::new();
+    if let Some(minutes) = arg_value("--autosave-minutes").and_then(|m| m.parse::<u64>().ok()) {
+        settings.interval = match minutes {
+            0 => None,
+            _ => Some(Duration::from_secs(minutes * 60)),
+        };
+    }
+    if let Some(count) = arg_value("--autosaves").and_then(|n| n.parse().ok()) {
+        settings.count = count;
+    }
+    settings
+}
+
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.windows(2)
+        .find(|pair| pair[0] == name)
+        .map(|pair| pair[1].clone())
 }
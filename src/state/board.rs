@@ -1,161 +1,524 @@
-const BOARD_WIDTH: u32 = 100;
-const BOARD_HEIGHT: u32 = 100;
+use super::company::Money;
+use std::fmt;
+
+pub const MIN_BOARD_SIZE: u32 = 64;
+pub const MAX_BOARD_SIZE: u32 = 1024;
+pub const DEFAULT_BOARD_SIZE: u32 = 100;
 
 pub const WATER_LEVEL: u8 = 0;
+pub const MIN_HEIGHT: VertexHeight = 0;
+pub const MAX_HEIGHT: VertexHeight = 6;
+
+/// How far above water level the ground must be for a tunnel to pass through it.
+const TUNNEL_COVER: VertexHeight = 2;
 
 pub type StructureDimension = u8;
 pub type VertexHeight = u8;
 pub type VertexPosition = u32;
 pub type BlockPosition = u32;
 
+#[derive(Clone)]
 pub struct Board {
-    vertices: [VertexHeight; ((BOARD_WIDTH + 1) * (BOARD_HEIGHT + 1)) as usize],
-    structures: Vec<StructurePlacement>,
-    block_occupants: [Option<usize>; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
+    width: u32,
+    height: u32,
+    vertices: Vec<VertexHeight>,
+    /// Demolished structures leave an empty slot behind so the indices of every other structure,
+    /// which `block_occupants` refers to, stay the same. Empty slots are reused by later placements.
+    structures: Vec<StructureSlot>,
+    free_slots: Vec<usize>,
+    block_occupants: Vec<Option<usize>>,
 }
 
 impl Board {
-    pub fn new() -> Self {
-        let mut res = Self {
-            vertices: [WATER_LEVEL; ((BOARD_WIDTH + 1) * (BOARD_HEIGHT + 1)) as usize],
+    /// Creates a board of `width` x `height` blocks with every vertex at water level.
+    /// New games are limited to between MIN_BOARD_SIZE and MAX_BOARD_SIZE blocks per side but any
+    /// non-zero size is accepted here.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            vertices: vec![WATER_LEVEL; ((width + 1) * (height + 1)) as usize],
             structures: Vec::new(),
-            block_occupants: [None; (BOARD_WIDTH * BOARD_HEIGHT) as usize],
-        };
-
-        for y in 1..BOARD_HEIGHT {
-            for x in 1..BOARD_WIDTH {
-                res.vertices[Self::vertex_index(Vertex { x, y })] += 1;
-            }
+            free_slots: Vec::new(),
+            block_occupants: vec![None; (width * height) as usize],
         }
-
-        // Set a few vertices to WATER+1.
-        for &v in [
-            Self::vertex_index(Vertex { x: 5, y: 3 }),
-            Self::vertex_index(Vertex { x: 5, y: 4 }),
-            Self::vertex_index(Vertex { x: 5, y: 5 }),
-            Self::vertex_index(Vertex { x: 5, y: 6 }),
-            Self::vertex_index(Vertex { x: 6, y: 3 }),
-            Self::vertex_index(Vertex { x: 6, y: 4 }),
-            Self::vertex_index(Vertex { x: 6, y: 5 }),
-            Self::vertex_index(Vertex { x: 6, y: 6 }),
-            Self::vertex_index(Vertex { x: 7, y: 3 }),
-            Self::vertex_index(Vertex { x: 7, y: 4 }),
-            Self::vertex_index(Vertex { x: 7, y: 5 }),
-            Self::vertex_index(Vertex { x: 7, y: 6 }),
-            Self::vertex_index(Vertex { x: 8, y: 3 }),
-            Self::vertex_index(Vertex { x: 8, y: 4 }),
-            Self::vertex_index(Vertex { x: 8, y: 5 }),
-            Self::vertex_index(Vertex { x: 8, y: 6 }),
-            Self::vertex_index(Vertex { x: 9, y: 3 }),
-            Self::vertex_index(Vertex { x: 9, y: 4 }),
-            Self::vertex_index(Vertex { x: 9, y: 5 }),
-            Self::vertex_index(Vertex { x: 9, y: 6 }),
-            Self::vertex_index(Vertex { x: 7, y: 4 }),
-            Self::vertex_index(Vertex { x: 7, y: 5 }),
-            Self::vertex_index(Vertex { x: 8, y: 4 }),
-            Self::vertex_index(Vertex { x: 8, y: 5 }),
-        ]
-        .iter()
-        {
-            res.vertices[v] += 1;
-        }
-
-        res
     }
 
     pub fn width(&self) -> u32 {
-        BOARD_WIDTH
+        self.width
     }
 
     pub fn height(&self) -> u32 {
-        BOARD_HEIGHT
+        self.height
     }
 
     pub fn vertex_on_board(&self, v: Vertex) -> bool {
-        v.x <= BOARD_WIDTH && v.y <= BOARD_HEIGHT
+        v.x <= self.width && v.y <= self.height
     }
 
-    fn block_index(b: Block) -> usize {
-        ((b.y * BOARD_WIDTH) + b.x) as usize
+    fn block_index(&self, b: Block) -> usize {
+        ((b.y * self.width) + b.x) as usize
     }
 
-    fn vertex_index(v: Vertex) -> usize {
-        ((v.y * (BOARD_WIDTH + 1)) + v.x) as usize
+    fn vertex_index(&self, v: Vertex) -> usize {
+        ((v.y * (self.width + 1)) + v.x) as usize
     }
 
     pub fn vertex_height(&self, v: Vertex) -> VertexHeight {
-        self.vertices[Self::vertex_index(v)]
+        self.vertices[self.vertex_index(v)]
     }
 
     pub fn set_vertex_height(&mut self, v: Vertex, height: u8) {
-        self.vertices[Self::vertex_index(v)] = height;
+        let idx = self.vertex_index(v);
+        self.vertices[idx] = height;
     }
 
     pub fn block_land_type(&self, x: VertexPosition, y: VertexPosition) -> LandType {
-        for &h in [
-            self.vertex_height(Vertex { x, y }),
-            self.vertex_height(Vertex { x: x + 1, y }),
-            self.vertex_height(Vertex { x: x + 1, y: y + 1 }),
-            self.vertex_height(Vertex { x, y: y + 1 }),
-        ]
-        .iter()
+        land_type(&|v| self.vertex_height(v), x, y)
+    }
+
+    pub fn block_on_board(&self, b: Block) -> bool {
+        b.x < self.width && b.y < self.height
+    }
+
+    pub fn block_structure_type(&self, block: Block) -> Option<Structure> {
+        if !self.block_on_board(block) {
+            return None;
+        }
+
+        self.block_occupants[self.block_index(block)]
+            .and_then(|idx| self.structures[idx].placement)
+            .map(|placement| placement.structure)
+    }
+
+    /// Checks whether the structure could be placed with its top corner at `origin`.
+    pub fn can_place(
+        &self,
+        structure: Structure,
+        orientation: Direction,
+        origin: Block,
+    ) -> Result<(), PlacementError> {
+        self.can_place_on(structure, orientation, origin, |v| self.vertex_height(v))
+    }
+
+    /// Checks whether the structure could be placed once the ground has been reshaped, taking the
+    /// height of each vertex from `height_at` instead of the board.
+    pub fn can_place_on<F>(
+        &self,
+        structure: Structure,
+        orientation: Direction,
+        origin: Block,
+        height_at: F,
+    ) -> Result<(), PlacementError>
+    where
+        F: Fn(Vertex) -> VertexHeight,
+    {
+        let placement = StructurePlacement {
+            structure,
+            orientation,
+            origin,
+        };
+        let w = placement.width() as u32;
+        let h = placement.height() as u32;
+        if origin.x as u64 + w as u64 > self.width as u64
+            || origin.y as u64 + h as u64 > self.height as u64
         {
-            if h > WATER_LEVEL {
-                return LandType::Land;
+            return Err(PlacementError::OutOfBounds);
+        }
+
+        for y in origin.y..origin.y + h {
+            for x in origin.x..origin.x + w {
+                let block = Block { x, y };
+                if self.block_occupants[self.block_index(block)].is_some() {
+                    return Err(PlacementError::Overlap { block });
+                }
             }
         }
 
-        LandType::Water
+        match structure.terrain_rule() {
+            TerrainRule::AnyLand => check_land(&height_at, origin, w, h),
+            TerrainRule::FlatLand => {
+                check_land(&height_at, origin, w, h)?;
+                check_level(&height_at, origin, w, h)
+            }
+            TerrainRule::FlatLandByWater => {
+                check_land(&height_at, origin, w, h)?;
+                check_level(&height_at, origin, w, h)?;
+                self.check_adjacent_water(&height_at, origin, w, h)
+            }
+            TerrainRule::OverWater => {
+                for y in origin.y..origin.y + h {
+                    for x in origin.x..origin.x + w {
+                        if land_type(&height_at, x, y) == LandType::Land {
+                            return Err(PlacementError::NotOverWater {
+                                block: Block { x, y },
+                            });
+                        }
+                    }
+                }
+                Ok(())
+            }
+            TerrainRule::ThroughHill => {
+                for y in origin.y..(origin.y + h + 1) {
+                    for x in origin.x..(origin.x + w + 1) {
+                        if height_at(Vertex { x, y }) < WATER_LEVEL + TUNNEL_COVER {
+                            return Err(PlacementError::NoHill);
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
     }
 
-    pub fn block_structure_type(&self, block: Block) -> Option<Structure> {
-        match self.block_occupants[Self::block_index(block)] {
-            Some(idx) => Some(self.structures[idx].structure),
-            None => None,
+    /// Requires at least one water block directly beside the footprint, not counting diagonals.
+    fn check_adjacent_water<F>(
+        &self,
+        height_at: &F,
+        origin: Block,
+        w: u32,
+        h: u32,
+    ) -> Result<(), PlacementError>
+    where
+        F: Fn(Vertex) -> VertexHeight,
+    {
+        let is_water = |x: i64, y: i64| {
+            x >= 0
+                && y >= 0
+                && self.block_on_board(Block {
+                    x: x as u32,
+                    y: y as u32,
+                })
+                && land_type(height_at, x as u32, y as u32) == LandType::Water
+        };
+
+        let (left, top) = (origin.x as i64, origin.y as i64);
+        let (right, bottom) = (left + w as i64, top + h as i64);
+        for x in left..right {
+            if is_water(x, top - 1) || is_water(x, bottom) {
+                return Ok(());
+            }
+        }
+        for y in top..bottom {
+            if is_water(left - 1, y) || is_water(right, y) {
+                return Ok(());
+            }
         }
+
+        Err(PlacementError::NoAdjacentWater)
     }
 
-    pub fn place_structure(&mut self, structure: Structure, orientation: Direction, origin: Block) {
-        let idx = self.structures.len();
+    pub fn place_structure(
+        &mut self,
+        structure: Structure,
+        orientation: Direction,
+        origin: Block,
+    ) -> Result<StructureId, PlacementError> {
+        self.can_place(structure, orientation, origin)?;
+
         let placement = StructurePlacement {
             structure,
             orientation,
             origin,
         };
-        self.structures.push(placement);
-        // Iterate over each block the structure covers and insert the index.
-        // TODO (toby): Pre-check if any block is already occupied and return a result.
+        let idx = match self.free_slots.pop() {
+            Some(idx) => idx,
+            None => {
+                self.structures.push(StructureSlot {
+                    generation: 0,
+                    placement: None,
+                });
+                self.structures.len() - 1
+            }
+        };
+        let slot = &mut self.structures[idx];
+        slot.placement = Some(placement);
+        let id = StructureId {
+            index: idx,
+            generation: slot.generation,
+        };
+        self.set_occupant(&placement, Some(idx));
+
+        Ok(id)
+    }
+
+    /// Removes a structure from the board, freeing up every block it covered.
+    /// The id, and any copies of it, will no longer match anything on the board.
+    pub fn demolish(&mut self, id: StructureId) -> Option<StructurePlacement> {
+        let placement = self.structure(id).copied()?;
+        let slot = &mut self.structures[id.index];
+        slot.placement = None;
+        // Wrapping only lets a stale id match again after four billion demolitions of one slot.
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(id.index);
+        self.set_occupant(&placement, None);
+
+        Some(placement)
+    }
+
+    /// Removes whichever structure covers `block`.
+    pub fn demolish_at(&mut self, block: Block) -> Option<StructurePlacement> {
+        let id = self.structure_at(block)?;
+        self.demolish(id)
+    }
+
+    pub fn structure(&self, id: StructureId) -> Option<&StructurePlacement> {
+        match self.structures.get(id.index) {
+            Some(slot) if slot.generation == id.generation => slot.placement.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn structures(&self) -> impl Iterator<Item = (StructureId, &StructurePlacement)> {
+        self.structures
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                slot.placement.as_ref().map(|placement| {
+                    let id = StructureId {
+                        index,
+                        generation: slot.generation,
+                    };
+                    (id, placement)
+                })
+            })
+    }
+
+    /// The structure covering `block`, if any.
+    pub fn structure_at(&self, block: Block) -> Option<StructureId> {
+        if !self.block_on_board(block) {
+            return None;
+        }
+
+        let index = self.block_occupants[self.block_index(block)]?;
+        Some(StructureId {
+            index,
+            generation: self.structures[index].generation,
+        })
+    }
+
+    /// Every vertex height, row by row, with `width + 1` vertices to a row.
+    pub fn vertex_heights(&self) -> &[VertexHeight] {
+        &self.vertices
+    }
+
+    /// Every structure slot in order, as its generation and current placement.
+    pub fn structure_slots(&self) -> impl Iterator<Item = (u32, Option<&StructurePlacement>)> {
+        self.structures
+            .iter()
+            .map(|slot| (slot.generation, slot.placement.as_ref()))
+    }
+
+    /// The empty structure slots, in the order they will be reused.
+    pub fn free_slots(&self) -> &[usize] {
+        &self.free_slots
+    }
+
+    /// Rebuilds a board from the parts given by `vertex_heights`, `structure_slots` and
+    /// `free_slots`, so that every structure keeps the same id it had before.
+    pub fn restore(
+        width: u32,
+        height: u32,
+        vertices: Vec<VertexHeight>,
+        slots: Vec<(u32, Option<StructurePlacement>)>,
+        free_slots: Vec<usize>,
+    ) -> Result<Self, String> {
+        let mut board = Self::new(width, height);
+        if vertices.len() != board.vertices.len() {
+            return Err(format!(
+                "expected {} vertex heights but found {}",
+                board.vertices.len(),
+                vertices.len()
+            ));
+        }
+        board.vertices = vertices;
+
+        for (idx, &(generation, placement)) in slots.iter().enumerate() {
+            if let Some(placement) = placement {
+                let (w, h) = (placement.width() as u32, placement.height() as u32);
+                let origin = placement.origin;
+                if origin.x as u64 + w as u64 > width as u64
+                    || origin.y as u64 + h as u64 > height as u64
+                {
+                    return Err(format!("structure {} hangs off the board", idx));
+                }
+                for y in origin.y..origin.y + h {
+                    for x in origin.x..origin.x + w {
+                        if board.structure_at(Block { x, y }).is_some() {
+                            return Err(format!("structure {} overlaps another", idx));
+                        }
+                    }
+                }
+                board.set_occupant(&placement, Some(idx));
+            }
+            board.structures.push(StructureSlot {
+                generation,
+                placement,
+            });
+        }
+
+        let mut empty_slots: Vec<usize> = (0..slots.len())
+            .filter(|&idx| slots[idx].1.is_none())
+            .collect();
+        let mut listed_slots = free_slots.clone();
+        listed_slots.sort_unstable();
+        empty_slots.sort_unstable();
+        if listed_slots != empty_slots {
+            return Err(String::from(
+                "free slots do not match the empty structure slots",
+            ));
+        }
+        board.free_slots = free_slots;
+
+        Ok(board)
+    }
+
+    /// Points every block the placement covers at the given structure index.
+    fn set_occupant(&mut self, placement: &StructurePlacement, occupant: Option<usize>) {
+        let origin = placement.origin;
         let w = placement.width();
         let h = placement.height();
         for y in origin.y..origin.y + h as u32 {
             for x in origin.x..origin.x + w as u32 {
-                self.block_occupants[Self::block_index(Block { x, y })] = Some(idx);
+                let block_idx = self.block_index(Block { x, y });
+                self.block_occupants[block_idx] = occupant;
             }
         }
     }
 }
 
-#[derive(PartialEq, Copy, Clone)]
+/// Land if any corner of the block at (`x`, `y`) is above water level, taking each vertex's height
+/// from `height_at`.
+fn land_type<F>(height_at: &F, x: VertexPosition, y: VertexPosition) -> LandType
+where
+    F: Fn(Vertex) -> VertexHeight,
+{
+    for &h in [
+        height_at(Vertex { x, y }),
+        height_at(Vertex { x: x + 1, y }),
+        height_at(Vertex { x: x + 1, y: y + 1 }),
+        height_at(Vertex { x, y: y + 1 }),
+    ]
+    .iter()
+    {
+        if h > WATER_LEVEL {
+            return LandType::Land;
+        }
+    }
+
+    LandType::Water
+}
+
+fn check_land<F>(height_at: &F, origin: Block, w: u32, h: u32) -> Result<(), PlacementError>
+where
+    F: Fn(Vertex) -> VertexHeight,
+{
+    for y in origin.y..origin.y + h {
+        for x in origin.x..origin.x + w {
+            if land_type(height_at, x, y) == LandType::Water {
+                return Err(PlacementError::OnWater {
+                    block: Block { x, y },
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_level<F>(height_at: &F, origin: Block, w: u32, h: u32) -> Result<(), PlacementError>
+where
+    F: Fn(Vertex) -> VertexHeight,
+{
+    let level = height_at(origin.into());
+    for y in origin.y..(origin.y + h + 1) {
+        for x in origin.x..(origin.x + w + 1) {
+            if height_at(Vertex { x, y }) != level {
+                return Err(PlacementError::UnevenTerrain);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Identifies a structure placed on a board.
+/// An id stays valid for as long as its structure exists. Once the structure is demolished the id
+/// stops matching, even if a new structure takes over the same slot.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub struct StructureId {
+    index: usize,
+    generation: u32,
+}
+
+#[derive(Clone)]
+struct StructureSlot {
+    generation: u32,
+    placement: Option<StructurePlacement>,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum PlacementError {
+    OutOfBounds,
+    Overlap { block: Block },
+    OnWater { block: Block },
+    UnevenTerrain,
+    NoAdjacentWater,
+    NotOverWater { block: Block },
+    NoHill,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlacementError::OutOfBounds => write!(f, "structure would hang off the board"),
+            PlacementError::Overlap { block } => {
+                write!(f, "block {:?} is already occupied", block)
+            }
+            PlacementError::OnWater { block } => write!(f, "block {:?} is water", block),
+            PlacementError::UnevenTerrain => write!(f, "the ground is not level"),
+            PlacementError::NoAdjacentWater => write!(f, "there is no water alongside"),
+            PlacementError::NotOverWater { block } => write!(f, "block {:?} is not water", block),
+            PlacementError::NoHill => write!(f, "there is no hill to pass through"),
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub struct Block {
     pub x: BlockPosition,
     pub y: BlockPosition,
 }
 
+impl Block {
+    /// The top corner, width and height of the smallest rectangle of blocks containing both
+    /// this block and `other`.
+    pub fn span(&self, other: Block) -> (Block, u32, u32) {
+        let origin = Block {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+        };
+        let w = self.x.max(other.x) - origin.x + 1;
+        let h = self.y.max(other.y) - origin.y + 1;
+        (origin, w, h)
+    }
+}
+
 impl From<Vertex> for Block {
     fn from(v: Vertex) -> Self {
         Self { x: v.x, y: v.y }
     }
 }
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 pub struct Vertex {
     pub x: VertexPosition,
     pub y: VertexPosition,
 }
 
 impl Vertex {
-    pub fn is_edge_vertex(&self) -> bool {
-        self.x == 0 || self.y == 0 || self.x == BOARD_WIDTH + 1 || self.y == BOARD_HEIGHT + 1
+    pub fn is_edge_vertex(&self, board: &Board) -> bool {
+        self.x == 0 || self.y == 0 || self.x == board.width() || self.y == board.height()
     }
 }
 
@@ -165,33 +528,47 @@ impl From<Block> for Vertex {
     }
 }
 
+#[derive(PartialEq, Copy, Clone)]
 pub enum LandType {
     Water,
     Land,
 }
 
 #[derive(PartialEq, Clone, Copy)]
-struct StructurePlacement {
+pub struct StructurePlacement {
     structure: Structure,
     orientation: Direction,
     origin: Block,
 }
 
 impl StructurePlacement {
-    fn width(&self) -> StructureDimension {
-        let (w, h) = self.structure.size();
-        match self.orientation {
-            Direction::North | Direction::South => w,
-            Direction::East | Direction::West => h,
+    pub fn new(structure: Structure, orientation: Direction, origin: Block) -> Self {
+        Self {
+            structure,
+            orientation,
+            origin,
         }
     }
 
-    fn height(&self) -> StructureDimension {
-        let (w, h) = self.structure.size();
-        match self.orientation {
-            Direction::North | Direction::South => h,
-            Direction::East | Direction::West => w,
-        }
+    pub fn structure(&self) -> Structure {
+        self.structure
+    }
+
+    pub fn orientation(&self) -> Direction {
+        self.orientation
+    }
+
+    /// The top corner block of the structure.
+    pub fn origin(&self) -> Block {
+        self.origin
+    }
+
+    pub fn width(&self) -> StructureDimension {
+        self.structure.footprint(self.orientation).0
+    }
+
+    pub fn height(&self) -> StructureDimension {
+        self.structure.footprint(self.orientation).1
     }
 }
 
@@ -263,6 +640,83 @@ pub enum Structure {
 }
 
 impl Structure {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Structure::Forest => "Forest",
+            Structure::CityRoad => "City Road",
+            Structure::ApartmentBuilding => "Apartment Building",
+            Structure::CulturalCenter => "Cultural Center",
+            Structure::TennisCourt => "Tennis Court",
+            Structure::SwimmingPool => "Swimming Pool",
+            Structure::SportsStadium => "Sports Stadium",
+            Structure::RaceTrack => "Race Track",
+            Structure::University => "University",
+            Structure::AmusementPark => "Amusement Park",
+            Structure::LumberMill => "Lumber Mill",
+            Structure::ChemicalPlant => "Chemical Plant",
+            Structure::SteelMill => "Steel Mill",
+            Structure::Mine {
+                mineral: Mineral::Gold,
+            } => "Gold Mine",
+            Structure::Mine {
+                mineral: Mineral::Silver,
+            } => "Silver Mine",
+            Structure::Mine {
+                mineral: Mineral::Diamonds,
+            } => "Diamond Mine",
+            Structure::Street => "Street",
+            Structure::Rails => "Rails",
+            Structure::Bridge => "Bridge",
+            Structure::Tunnel => "Tunnel",
+            Structure::TruckDepot => "Truck Depot",
+            Structure::TrainStation => "Train Station",
+            Structure::TrainPlatform => "Train Platform",
+            Structure::Harbor => "Harbor",
+            Structure::Airport => "Airport",
+            Structure::AutomobileFactory => "Automobile Factory",
+            Structure::Woodshop => "Woodshop",
+            Structure::ElectronicsFactory => "Electronics Factory",
+            Structure::SportsEquipmentFactory => "Sports Equipment Factory",
+            Structure::ToyFactory => "Toy Factory",
+            Structure::JewelryFactory => "Jewelry Factory",
+            Structure::Warehouse => "Warehouse",
+            Structure::BuildingEquipmentFactory => "Building Equipment Factory",
+            Structure::PaperFactory => "Paper Factory",
+            Structure::PrintingPress => "Printing Press",
+            Structure::ToyStore => "Toy Store",
+            Structure::SportingGoodsStore => "Sporting Goods Store",
+            Structure::FurnitureStore => "Furniture Store",
+            Structure::Jeweler => "Jeweler",
+            Structure::ElectronicsStore => "Electronics Store",
+            Structure::CarDealership => "Car Dealership",
+            Structure::BuildingEquipmentStore => "Building Equipment Store",
+            Structure::StationaryStore => "Stationary Store",
+        }
+    }
+
+    pub fn terrain_rule(&self) -> TerrainRule {
+        match self {
+            Structure::Forest
+            | Structure::CityRoad
+            | Structure::Street
+            | Structure::Rails
+            | Structure::Mine { .. } => TerrainRule::AnyLand,
+            Structure::Bridge => TerrainRule::OverWater,
+            Structure::Tunnel => TerrainRule::ThroughHill,
+            Structure::Harbor => TerrainRule::FlatLandByWater,
+            _ => TerrainRule::FlatLand,
+        }
+    }
+
+    /// Width (along the x-axis) and height (along the y-axis) of the structure once rotated.
+    pub fn footprint(&self, orientation: Direction) -> (StructureDimension, StructureDimension) {
+        let (w, h) = self.size();
+        match orientation {
+            Direction::North | Direction::South => (w, h),
+            Direction::East | Direction::West => (h, w),
+        }
+    }
+
     pub fn size(&self) -> (StructureDimension, StructureDimension) {
         match self {
             Structure::Forest => (1, 1),
@@ -308,6 +762,64 @@ impl Structure {
             Structure::StationaryStore => (2, 2),
         }
     }
+
+    /// What it costs to build.
+    pub fn cost(&self) -> Money {
+        match self {
+            Structure::Forest => 1_000,
+            Structure::CityRoad => 2_000,
+            Structure::ApartmentBuilding => 40_000,
+            Structure::CulturalCenter => 60_000,
+            Structure::TennisCourt => 30_000,
+            Structure::SwimmingPool => 40_000,
+            Structure::SportsStadium => 250_000,
+            Structure::RaceTrack => 200_000,
+            Structure::University => 300_000,
+            Structure::AmusementPark => 400_000,
+            Structure::LumberMill => 150_000,
+            Structure::ChemicalPlant => 300_000,
+            Structure::SteelMill => 350_000,
+            Structure::Mine { .. } => 120_000,
+            Structure::Street => 1_000,
+            Structure::Rails => 2_500,
+            Structure::Bridge => 20_000,
+            Structure::Tunnel => 30_000,
+            Structure::TruckDepot => 25_000,
+            Structure::TrainStation => 80_000,
+            Structure::TrainPlatform => 20_000,
+            Structure::Harbor => 150_000,
+            Structure::Airport => 500_000,
+            Structure::AutomobileFactory => 400_000,
+            Structure::Woodshop => 100_000,
+            Structure::ElectronicsFactory => 250_000,
+            Structure::SportsEquipmentFactory => 150_000,
+            Structure::ToyFactory => 120_000,
+            Structure::JewelryFactory => 150_000,
+            Structure::Warehouse => 60_000,
+            Structure::BuildingEquipmentFactory => 180_000,
+            Structure::PaperFactory => 140_000,
+            Structure::PrintingPress => 130_000,
+            Structure::ToyStore => 30_000,
+            Structure::SportingGoodsStore => 30_000,
+            Structure::FurnitureStore => 35_000,
+            Structure::Jeweler => 40_000,
+            Structure::ElectronicsStore => 40_000,
+            Structure::CarDealership => 60_000,
+            Structure::BuildingEquipmentStore => 50_000,
+            Structure::StationaryStore => 25_000,
+        }
+    }
+}
+
+/// What the ground under, and around, a structure needs to look like.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TerrainRule {
+    AnyLand,
+    FlatLand,
+    /// Flat land with water directly beside it.
+    FlatLandByWater,
+    OverWater,
+    ThroughHill,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -317,3 +829,159 @@ pub enum Direction {
     South,
     West,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board with every vertex raised to `height`, so the whole of it is level land.
+    fn level_board(height: VertexHeight) -> Board {
+        let mut board = Board::new(MIN_BOARD_SIZE, MIN_BOARD_SIZE);
+        for y in 0..=board.height() {
+            for x in 0..=board.width() {
+                board.set_vertex_height(Vertex { x, y }, height);
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn demolished_ids_stop_matching_once_their_slot_is_reused() {
+        let mut board = level_board(1);
+        let origin = Block { x: 4, y: 4 };
+        let old = board
+            .place_structure(Structure::TennisCourt, Direction::North, origin)
+            .unwrap();
+        assert!(board.demolish(old).is_some());
+        assert!(board.structure(old).is_none());
+
+        let new = board
+            .place_structure(Structure::SwimmingPool, Direction::North, origin)
+            .unwrap();
+        assert_ne!(new, old);
+        assert!(board.structure(old).is_none());
+        assert!(board.demolish(old).is_none());
+        assert!(board.structure(new).is_some());
+        assert_eq!(board.structure_at(origin), Some(new));
+    }
+
+    #[test]
+    fn placements_hanging_off_the_board_are_refused() {
+        let mut board = level_board(1);
+        let last = MIN_BOARD_SIZE - 1;
+        assert_eq!(
+            board.place_structure(
+                Structure::Street,
+                Direction::North,
+                Block { x: last + 1, y: 0 }
+            ),
+            Err(PlacementError::OutOfBounds)
+        );
+        // A 4x2 station turned to face east needs 2 blocks across and 4 down.
+        assert_eq!(
+            board.place_structure(
+                Structure::TrainStation,
+                Direction::East,
+                Block {
+                    x: last - 1,
+                    y: last - 2
+                }
+            ),
+            Err(PlacementError::OutOfBounds)
+        );
+        assert!(board
+            .place_structure(
+                Structure::TrainStation,
+                Direction::East,
+                Block {
+                    x: last - 1,
+                    y: last - 3
+                }
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn placements_over_another_structure_are_refused() {
+        let mut board = level_board(1);
+        board
+            .place_structure(
+                Structure::TennisCourt,
+                Direction::North,
+                Block { x: 4, y: 4 },
+            )
+            .unwrap();
+        assert_eq!(
+            board.place_structure(
+                Structure::TennisCourt,
+                Direction::North,
+                Block { x: 5, y: 3 }
+            ),
+            Err(PlacementError::Overlap {
+                block: Block { x: 5, y: 4 }
+            })
+        );
+    }
+
+    #[test]
+    fn placements_on_water_are_refused() {
+        let mut board = Board::new(MIN_BOARD_SIZE, MIN_BOARD_SIZE);
+        assert_eq!(
+            board.place_structure(Structure::Forest, Direction::North, Block { x: 4, y: 4 }),
+            Err(PlacementError::OnWater {
+                block: Block { x: 4, y: 4 }
+            })
+        );
+        assert!(board.structures().next().is_none());
+    }
+
+    #[test]
+    fn placements_on_uneven_ground_are_refused() {
+        let mut board = level_board(1);
+        board.set_vertex_height(Vertex { x: 6, y: 5 }, 2);
+        assert_eq!(
+            board.place_structure(
+                Structure::TennisCourt,
+                Direction::North,
+                Block { x: 4, y: 4 }
+            ),
+            Err(PlacementError::UnevenTerrain)
+        );
+        // Land-only structures don't mind the slope.
+        assert!(board
+            .place_structure(
+                Structure::Mine {
+                    mineral: Mineral::Gold
+                },
+                Direction::North,
+                Block { x: 4, y: 4 }
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn generations_wrap_instead_of_overflowing() {
+        let placement = StructurePlacement::new(
+            Structure::TennisCourt,
+            Direction::North,
+            Block { x: 4, y: 4 },
+        );
+        let mut board = Board::restore(
+            MIN_BOARD_SIZE,
+            MIN_BOARD_SIZE,
+            level_board(1).vertex_heights().to_vec(),
+            vec![(u32::MAX, Some(placement))],
+            Vec::new(),
+        )
+        .unwrap();
+        let old = board.structure_at(placement.origin()).unwrap();
+        assert!(board.demolish(old).is_some());
+
+        let new = board
+            .place_structure(Structure::TennisCourt, Direction::North, placement.origin())
+            .unwrap();
+        assert_ne!(new, old);
+        assert!(board.structure(old).is_none());
+        assert_eq!(board.structure_slots().next().map(|(g, _)| g), Some(0));
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The number of simulation ticks in a game day.
+pub const TICKS_PER_DAY: u32 = 240;
+
+/// How fast the simulation runs, as the number of steps it takes for each update of the game loop.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GameSpeed {
+    X1,
+    X2,
+    X4,
+    X8,
+}
+
+impl GameSpeed {
+    pub fn steps(self) -> u32 {
+        match self {
+            GameSpeed::X1 => 1,
+            GameSpeed::X2 => 2,
+            GameSpeed::X4 => 4,
+            GameSpeed::X8 => 8,
+        }
+    }
+}
+
+impl fmt::Display for GameSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x", self.steps())
+    }
+}
+
+/// The day every new game starts on.
+pub const START_DATE: GameDate = GameDate {
+    year: 1950,
+    month: 1,
+    day: 1,
+};
+
+/// A day in the game's calendar. Months run from 1 to 12 and days from 1.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct GameDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl GameDate {
+    /// The day after this one.
+    pub fn next_day(self) -> Self {
+        if self.day < days_in_month(self.year, self.month) {
+            Self {
+                day: self.day + 1,
+                ..self
+            }
+        } else if self.month < 12 {
+            Self {
+                month: self.month + 1,
+                day: 1,
+                ..self
+            }
+        } else {
+            Self {
+                year: self.year + 1,
+                month: 1,
+                day: 1,
+            }
+        }
+    }
+}
+
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+impl fmt::Display for GameDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let month = MONTH_NAMES
+            .get(self.month as usize - 1)
+            .copied()
+            .unwrap_or("???");
+        write!(f, "{} {} {}", self.day, month, self.year)
+    }
+}
@@ -0,0 +1,229 @@
+/// Amounts of money, in whole dollars. Negative amounts are debts.
+pub type Money = i64;
+
+use std::collections::VecDeque;
+
+pub const STARTING_CASH: Money = 1_000_000;
+/// The percentage of a structure's cost paid back when it is demolished, unless the game sets
+/// another.
+pub const DEFAULT_DEMOLITION_REFUND: u8 = 50;
+/// Loans are taken out and repaid in steps of this much.
+pub const LOAN_STEP: Money = 50_000;
+/// The percentage of the company's value the bank will lend.
+pub const LOAN_LIMIT_PERCENT: Money = 50;
+/// Interest on loans, charged monthly.
+pub const ANNUAL_INTEREST_PERCENT: Money = 6;
+/// How many days in a row the company's cash can stay below zero before it goes bankrupt.
+pub const BANKRUPTCY_DAYS: u16 = 90;
+/// How many closed months and years the ledger keeps.
+pub const LEDGER_MONTHS: usize = 24;
+pub const LEDGER_YEARS: usize = 10;
+
+pub struct Company {
+    pub cash: Money,
+    pub ledger: Ledger,
+    /// What the company owes the bank.
+    pub loan: Money,
+    /// How many days in a row cash has been below zero.
+    pub days_in_debt: u16,
+    /// Whether the company has stayed in debt too long. The game is over once it has.
+    pub bankrupt: bool,
+}
+
+impl Company {
+    pub fn new() -> Self {
+        Self {
+            cash: STARTING_CASH,
+            ledger: Ledger::new(),
+            loan: 0,
+            days_in_debt: 0,
+            bankrupt: false,
+        }
+    }
+
+    /// Checks the company has the cash to pay the given cost.
+    pub fn check_funds(&self, cost: Money) -> Result<(), String> {
+        if cost > self.cash {
+            Err(format!(
+                "it costs {} and the company only has {}",
+                format_money(cost),
+                format_money(self.cash)
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Pays or receives money, entering it in the ledger. Positive amounts are income and negative
+    /// amounts are expenses.
+    pub fn record(&mut self, category: LedgerCategory, amount: Money) {
+        self.cash += amount;
+        self.ledger.record(category, amount);
+    }
+}
+
+impl Default for Company {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What money was made or spent on.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LedgerCategory {
+    Construction,
+    Terraforming,
+    /// Running costs, including interest on loans.
+    Operations,
+    Sales,
+}
+
+pub const LEDGER_CATEGORIES: [LedgerCategory; 4] = [
+    LedgerCategory::Construction,
+    LedgerCategory::Terraforming,
+    LedgerCategory::Operations,
+    LedgerCategory::Sales,
+];
+
+impl LedgerCategory {
+    pub fn name(self) -> &'static str {
+        match self {
+            LedgerCategory::Construction => "Construction",
+            LedgerCategory::Terraforming => "Terraforming",
+            LedgerCategory::Operations => "Operations",
+            LedgerCategory::Sales => "Sales",
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            LedgerCategory::Construction => 0,
+            LedgerCategory::Terraforming => 1,
+            LedgerCategory::Operations => 2,
+            LedgerCategory::Sales => 3,
+        }
+    }
+}
+
+/// The money made and spent over a month or a year, by category. Both are kept as positive
+/// amounts.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LedgerTotals {
+    income: [Money; 4],
+    expenses: [Money; 4],
+}
+
+impl LedgerTotals {
+    pub fn new() -> Self {
+        Self {
+            income: [0; 4],
+            expenses: [0; 4],
+        }
+    }
+
+    pub fn income(&self, category: LedgerCategory) -> Money {
+        self.income[category.index()]
+    }
+
+    pub fn expenses(&self, category: LedgerCategory) -> Money {
+        self.expenses[category.index()]
+    }
+
+    pub fn total_income(&self) -> Money {
+        self.income.iter().sum()
+    }
+
+    pub fn total_expenses(&self) -> Money {
+        self.expenses.iter().sum()
+    }
+
+    pub fn profit(&self) -> Money {
+        self.total_income() - self.total_expenses()
+    }
+
+    pub fn add_income(&mut self, category: LedgerCategory, amount: Money) {
+        self.income[category.index()] += amount;
+    }
+
+    pub fn add_expenses(&mut self, category: LedgerCategory, amount: Money) {
+        self.expenses[category.index()] += amount;
+    }
+
+    fn record(&mut self, category: LedgerCategory, amount: Money) {
+        if amount >= 0 {
+            self.add_income(category, amount);
+        } else {
+            self.add_expenses(category, -amount);
+        }
+    }
+}
+
+impl Default for LedgerTotals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The company's income and expenses for the month and year in progress, and for those gone by.
+pub struct Ledger {
+    pub this_month: LedgerTotals,
+    pub this_year: LedgerTotals,
+    /// Closed months as (year, month, totals), most recent first.
+    pub past_months: VecDeque<(u16, u8, LedgerTotals)>,
+    /// Closed years as (year, totals), most recent first.
+    pub past_years: VecDeque<(u16, LedgerTotals)>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self {
+            this_month: LedgerTotals::new(),
+            this_year: LedgerTotals::new(),
+            past_months: VecDeque::new(),
+            past_years: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, category: LedgerCategory, amount: Money) {
+        self.this_month.record(category, amount);
+        self.this_year.record(category, amount);
+    }
+
+    /// Files the month in progress under the given year and month and starts a new one.
+    pub fn close_month(&mut self, year: u16, month: u8) {
+        self.past_months.push_front((year, month, self.this_month));
+        self.past_months.truncate(LEDGER_MONTHS);
+        self.this_month = LedgerTotals::new();
+    }
+
+    /// Files the year in progress under the given year and starts a new one.
+    pub fn close_year(&mut self, year: u16) {
+        self.past_years.push_front((year, self.this_year));
+        self.past_years.truncate(LEDGER_YEARS);
+        self.this_year = LedgerTotals::new();
+    }
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats an amount for display, e.g. `-$1,250,000`.
+pub fn format_money(amount: Money) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (idx, c) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+
+    if amount < 0 {
+        format!("-${}", grouped)
+    } else {
+        format!("${}", grouped)
+    }
+}
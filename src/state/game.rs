@@ -1,32 +1,109 @@
-use super::board::{Block, Board, Direction, Structure, Vertex};
+use super::board::{Block, Board, Direction, Structure, Vertex, VertexHeight, MAX_HEIGHT};
+use super::calendar::{GameDate, GameSpeed, START_DATE};
+use super::company::{Company, Money, DEFAULT_DEMOLITION_REFUND};
+use super::history::History;
 use super::menu::building::BuyBuildingScreenState;
+use super::menu::finances::FinancesScreenState;
+use super::menu::load::LoadScreenState;
 use super::menu::sidebar::SidebarMenu;
+use super::terrain::{StructurePolicy, TerrainError, TerrainPlan};
+
+/// The slot a new game is saved into unless the player names another.
+pub const DEFAULT_SAVE_NAME: &str = "quicksave";
+/// The longest name the player can type for a save slot, in characters.
+pub const MAX_SAVE_NAME_LENGTH: usize = 32;
 
 pub struct GameState {
     pub board: Board,
+    /// Whether terraforming demolishes structures in its way rather than refusing.
+    pub bulldoze: bool,
     pub buy_building_screen: BuyBuildingScreenState,
+    pub company: Company,
+    /// The costs of what the player is lining up, shown before anything is committed.
+    pub cost_preview: CostPreview,
+    pub date: GameDate,
+    /// Ticks since the current day began. Saves only keep the date, so a loaded game starts its
+    /// day afresh.
+    pub day_ticks: u32,
+    /// The percentage of a structure's cost paid back when it is demolished or bulldozed.
+    pub demolition_refund: u8,
+    pub finances_screen: FinancesScreenState,
     pub focal_point: Vertex,
     pub highlighted_block: Option<Block>,
     pub highlighted_button: Option<SidebarMenu>,
+    /// Edits the player can undo and redo.
+    pub history: History,
+    pub load_screen: LoadScreenState,
     pub open_menu: Option<SidebarMenu>,
+    /// Whether the player has paused the simulation. See `is_paused` for whether it is running.
+    pub paused: bool,
+    /// A placement waiting on the player to confirm that the ground under it should be levelled.
+    pub pending_flatten: Option<PendingPlacement>,
     pub player_mode: PlayerMode,
+    /// The name of the slot manual saves of this game go into.
+    pub save_name: String,
+    pub speed: GameSpeed,
+    /// The outcome of the terrain operation the player is lining up, shown before it is committed.
+    pub terrain_preview: Option<Result<TerrainPlan, TerrainError>>,
 }
 
 impl GameState {
-    pub fn new() -> Self {
+    pub fn new(board: Board) -> Self {
+        // Kept on the board so saves of small maps are still accepted.
+        let focal_point = Vertex {
+            x: board.width().min(10),
+            y: board.height().min(20),
+        };
         Self {
-            board: Board::new(),
+            board,
+            bulldoze: false,
             buy_building_screen: BuyBuildingScreenState::Hidden,
-            focal_point: Vertex { x: 10, y: 20 },
+            company: Company::new(),
+            cost_preview: CostPreview::new(),
+            date: START_DATE,
+            day_ticks: 0,
+            demolition_refund: DEFAULT_DEMOLITION_REFUND,
+            finances_screen: FinancesScreenState::Hidden,
+            focal_point,
             // player_mode: PlayerMode::Focus,
             player_mode: PlayerMode::PlaceStructure {
                 structure: Structure::Forest,
                 orientation: Direction::North,
             },
-            // player_mode: PlayerMode::RaiseLower { radius: 0 },
+            // player_mode: PlayerMode::RaiseLower { brush: Brush::new() },
             highlighted_block: None,
             highlighted_button: None,
+            history: History::new(),
+            load_screen: LoadScreenState::Hidden,
             open_menu: None,
+            paused: false,
+            pending_flatten: None,
+            save_name: String::from(DEFAULT_SAVE_NAME),
+            speed: GameSpeed::X1,
+            terrain_preview: None,
+        }
+    }
+
+    /// Whether the simulation is held, either by the player, because a modal screen is open or
+    /// because the company has gone bankrupt.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+            || self.company.bankrupt
+            || self.buy_building_screen != BuyBuildingScreenState::Hidden
+            || self.finances_screen != FinancesScreenState::Hidden
+            || matches!(self.load_screen, LoadScreenState::Visible { .. })
+    }
+
+    /// What demolishing a structure pays back.
+    pub fn refund(&self, structure: Structure) -> Money {
+        structure.cost() * self.demolition_refund as Money / 100
+    }
+
+    pub fn structure_policy(&self) -> StructurePolicy {
+        if self.bulldoze {
+            StructurePolicy::Bulldoze
+        } else {
+            StructurePolicy::Protect
         }
     }
 
@@ -37,32 +114,185 @@ impl GameState {
                 structure,
                 orientation,
             } => {
-                let (w, h) = structure.size();
-                match orientation {
-                    Direction::North | Direction::South => SelectionMode::Blocks { w, h },
-                    Direction::East | Direction::West => SelectionMode::Blocks { w: h, h: w },
-                }
+                let (w, h) = structure.footprint(orientation);
+                SelectionMode::Blocks { w, h }
             }
-            PlayerMode::RaiseLower { radius } => SelectionMode::Vertex { radius },
+            PlayerMode::RaiseLower { brush } => SelectionMode::Vertex { brush },
+            PlayerMode::Demolish => SelectionMode::Blocks { w: 1, h: 1 },
+            PlayerMode::Flatten {
+                anchor: Some(anchor),
+                ..
+            } => SelectionMode::Area { anchor },
+            PlayerMode::Flatten { anchor: None, .. } => SelectionMode::Blocks { w: 1, h: 1 },
         }
     }
 }
 
+#[derive(PartialEq, Clone, Debug)]
+pub struct CostPreview {
+    /// Each cost, labelled. Money that would be paid back is a negative cost.
+    pub lines: Vec<(&'static str, Money)>,
+    /// Each amount the company could be charged, with costs charged together summed into one.
+    pub charges: Vec<Money>,
+    /// Whether the company is short of cash for any of the charges.
+    pub unaffordable: bool,
+}
+
+impl CostPreview {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            charges: Vec::new(),
+            unaffordable: false,
+        }
+    }
+}
+
+impl Default for CostPreview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum PlayerMode {
     Focus,
+    Demolish,
     RaiseLower {
-        radius: u8,
+        brush: Brush,
     },
     PlaceStructure {
         structure: Structure,
         orientation: Direction,
     },
+    /// Levelling a rectangle of blocks. The rectangle runs from the anchor, once one has been
+    /// picked, to the highlighted block.
+    Flatten {
+        anchor: Option<Block>,
+        level: FlattenLevel,
+    },
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum FlattenLevel {
+    /// The average height of the area being levelled.
+    Average,
+    Height(VertexHeight),
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub struct PendingPlacement {
+    pub structure: Structure,
+    pub orientation: Direction,
+    pub origin: Block,
+    /// What levelling the ground would cost, worked out when the levelling was offered.
+    pub level_cost: Money,
 }
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum SelectionMode {
     None,
-    Vertex { radius: u8 },
-    Blocks { w: u8, h: u8 },
+    Vertex {
+        brush: Brush,
+    },
+    Blocks {
+        w: u8,
+        h: u8,
+    },
+    /// Every block between the anchor and the highlighted block.
+    Area {
+        anchor: Block,
+    },
+}
+
+pub const MAX_BRUSH_RADIUS: u8 = 8;
+
+/// The area of vertices affected by a single raise or lower.
+#[derive(PartialEq, Clone, Copy)]
+pub struct Brush {
+    pub radius: u8,
+    pub shape: BrushShape,
+    pub falloff: BrushFalloff,
+}
+
+impl Brush {
+    pub fn new() -> Self {
+        Self {
+            radius: 0,
+            shape: BrushShape::Square,
+            falloff: BrushFalloff::None,
+        }
+    }
+
+    /// Distance from the centre of the brush to the vertex at the given offset, or None if the
+    /// vertex lies outside the brush.
+    pub fn distance(&self, dx: i32, dy: i32) -> Option<u8> {
+        let radius = self.radius as i32;
+        let distance = match self.shape {
+            BrushShape::Square => dx.abs().max(dy.abs()),
+            BrushShape::Circle => (((dx * dx + dy * dy) as f32).sqrt().round()) as i32,
+        };
+
+        if distance <= radius {
+            Some(distance as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Number of height units the vertex at the given offset moves by, or None if the vertex lies
+    /// outside the brush.
+    pub fn strength(&self, dx: i32, dy: i32) -> Option<u8> {
+        let distance = self.distance(dx, dy)?;
+        match self.falloff {
+            BrushFalloff::None => Some(1),
+            // Peaks at the centre and drops by one per vertex to a single unit at the rim. Nothing
+            // moves further than the whole height range, however wide the brush.
+            BrushFalloff::Smooth => {
+                let strength = self.radius as u32 + 1 - distance as u32;
+                Some(strength.min(MAX_HEIGHT as u32) as u8)
+            }
+        }
+    }
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum BrushShape {
+    Square,
+    Circle,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum BrushFalloff {
+    None,
+    Smooth,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smooth_brushes_fall_off_within_the_height_range() {
+        let brush = Brush {
+            radius: MAX_BRUSH_RADIUS,
+            shape: BrushShape::Square,
+            falloff: BrushFalloff::Smooth,
+        };
+        assert_eq!(brush.strength(0, 0), Some(MAX_HEIGHT));
+        assert_eq!(brush.strength(MAX_BRUSH_RADIUS as i32, 0), Some(1));
+        assert_eq!(brush.strength(MAX_BRUSH_RADIUS as i32 + 1, 0), None);
+
+        let wide = Brush {
+            radius: u8::MAX,
+            ..brush
+        };
+        assert_eq!(wide.strength(0, 0), Some(MAX_HEIGHT));
+    }
 }
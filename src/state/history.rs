@@ -0,0 +1,116 @@
+use super::board::StructurePlacement;
+use super::company::{LedgerCategory, Money};
+use super::terrain::VertexChange;
+use std::collections::VecDeque;
+
+/// How many edits can be undone before the oldest are forgotten.
+pub const MAX_UNDO: usize = 100;
+
+/// A change the player made to the board, holding everything needed to make it again or to
+/// reverse it. Structures are identified by their placement rather than their id, since a
+/// structure put back by an undo gets a new id.
+#[derive(PartialEq, Clone)]
+pub enum Edit {
+    /// Vertex height changes, along with the structures removed before the heights change and
+    /// the structures put back afterwards.
+    Terrain {
+        changes: Vec<VertexChange>,
+        demolished: Vec<StructurePlacement>,
+        restored: Vec<StructurePlacement>,
+    },
+    Place {
+        placement: StructurePlacement,
+    },
+    Demolish {
+        placement: StructurePlacement,
+    },
+    /// Money paid or received for the other edits in a group, so undoing them settles up too.
+    Payment {
+        category: LedgerCategory,
+        amount: Money,
+    },
+    /// Edits made together by a single action, in the order they were made.
+    Group(Vec<Edit>),
+}
+
+impl Edit {
+    /// The edit that exactly reverses this one.
+    pub fn inverse(&self) -> Edit {
+        match self {
+            Edit::Terrain {
+                changes,
+                demolished,
+                restored,
+            } => Edit::Terrain {
+                changes: changes
+                    .iter()
+                    .rev()
+                    .map(|change| VertexChange {
+                        vertex: change.vertex,
+                        from: change.to,
+                        to: change.from,
+                    })
+                    .collect(),
+                demolished: restored.clone(),
+                restored: demolished.clone(),
+            },
+            Edit::Place { placement } => Edit::Demolish {
+                placement: *placement,
+            },
+            Edit::Demolish { placement } => Edit::Place {
+                placement: *placement,
+            },
+            Edit::Payment { category, amount } => Edit::Payment {
+                category: *category,
+                amount: -amount,
+            },
+            Edit::Group(edits) => Edit::Group(edits.iter().rev().map(Edit::inverse).collect()),
+        }
+    }
+}
+
+/// The edits that can be undone, most recent last, and those undone which can be redone.
+pub struct History {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Remembers a new edit. Anything undone before it can no longer be redone.
+    pub fn record(&mut self, edit: Edit) {
+        self.redo.clear();
+        self.push_undo(edit);
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Edit> {
+        self.undo.pop_back()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Edit> {
+        self.redo.pop()
+    }
+
+    pub fn push_undo(&mut self, edit: Edit) {
+        if self.undo.len() == MAX_UNDO {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(edit);
+    }
+
+    pub fn push_redo(&mut self, edit: Edit) {
+        self.redo.push(edit);
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
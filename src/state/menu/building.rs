@@ -1,12 +1,32 @@
+use crate::state::board::Structure;
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum BuyBuildingScreenState {
     Visible {
         selected_building: Building,
         selected_category: Category,
+        /// The button under the cursor.
+        highlighted: Option<BuyBuildingButton>,
     },
     Hidden,
 }
 
+/// Something on the Buy Building screen the player can point at and click.
+#[derive(PartialEq, Copy, Clone)]
+pub enum BuyBuildingButton {
+    Category(Category),
+    Building(Building),
+    Buy,
+    Close,
+}
+
+pub const CATEGORIES: [Category; 4] = [
+    Category::Terminals,
+    Category::Production,
+    Category::Retail,
+    Category::CityBuildings,
+];
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum Category {
     Terminals,
@@ -54,3 +74,98 @@ pub enum Building {
     University,
     AmusementPark,
 }
+
+impl Category {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Terminals => "Terminals",
+            Category::Production => "Production",
+            Category::Retail => "Retail",
+            Category::CityBuildings => "Buildings (city)",
+        }
+    }
+
+    /// The buildings listed under the category, in the order they're shown.
+    pub fn buildings(&self) -> &'static [Building] {
+        match self {
+            Category::Terminals => &[
+                Building::TruckDepot,
+                Building::TrainStation,
+                Building::Airport,
+                Building::Harbor,
+            ],
+            Category::Production => &[
+                Building::AutomobileFactory,
+                Building::Woodshop,
+                Building::ElectronicsFactory,
+                Building::SportsEquipmentFactory,
+                Building::ToyFactory,
+                Building::JewelryFactory,
+                Building::Warehouse,
+                Building::BuildingEquipmentFactory,
+                Building::PaperFactory,
+                Building::PrintingPress,
+            ],
+            Category::Retail => &[
+                Building::ToyStore,
+                Building::SportingGoodsStore,
+                Building::FurnitureStore,
+                Building::Jeweler,
+                Building::ElectronicsStore,
+                Building::CarDealership,
+                Building::BuildingEquipmentStore,
+                Building::StationaryStore,
+            ],
+            Category::CityBuildings => &[
+                Building::CulturalCenter,
+                Building::TennisCourt,
+                Building::SwimmingPool,
+                Building::SportsStadium,
+                Building::RaceTrack,
+                Building::University,
+                Building::AmusementPark,
+            ],
+        }
+    }
+}
+
+impl Building {
+    pub fn name(&self) -> &'static str {
+        self.structure().name()
+    }
+
+    /// The structure placed on the board when the building is bought.
+    pub fn structure(&self) -> Structure {
+        match self {
+            Building::TruckDepot => Structure::TruckDepot,
+            Building::TrainStation => Structure::TrainStation,
+            Building::Airport => Structure::Airport,
+            Building::Harbor => Structure::Harbor,
+            Building::AutomobileFactory => Structure::AutomobileFactory,
+            Building::Woodshop => Structure::Woodshop,
+            Building::ElectronicsFactory => Structure::ElectronicsFactory,
+            Building::SportsEquipmentFactory => Structure::SportsEquipmentFactory,
+            Building::ToyFactory => Structure::ToyFactory,
+            Building::JewelryFactory => Structure::JewelryFactory,
+            Building::Warehouse => Structure::Warehouse,
+            Building::BuildingEquipmentFactory => Structure::BuildingEquipmentFactory,
+            Building::PaperFactory => Structure::PaperFactory,
+            Building::PrintingPress => Structure::PrintingPress,
+            Building::ToyStore => Structure::ToyStore,
+            Building::SportingGoodsStore => Structure::SportingGoodsStore,
+            Building::FurnitureStore => Structure::FurnitureStore,
+            Building::Jeweler => Structure::Jeweler,
+            Building::ElectronicsStore => Structure::ElectronicsStore,
+            Building::CarDealership => Structure::CarDealership,
+            Building::BuildingEquipmentStore => Structure::BuildingEquipmentStore,
+            Building::StationaryStore => Structure::StationaryStore,
+            Building::CulturalCenter => Structure::CulturalCenter,
+            Building::TennisCourt => Structure::TennisCourt,
+            Building::SwimmingPool => Structure::SwimmingPool,
+            Building::SportsStadium => Structure::SportsStadium,
+            Building::RaceTrack => Structure::RaceTrack,
+            Building::University => Structure::University,
+            Building::AmusementPark => Structure::AmusementPark,
+        }
+    }
+}
@@ -0,0 +1,5 @@
+#[derive(PartialEq, Clone, Copy)]
+pub enum FinancesScreenState {
+    Visible,
+    Hidden,
+}
@@ -0,0 +1,11 @@
+use crate::storage::slots::SlotSummary;
+
+pub enum LoadScreenState {
+    Visible {
+        slots: Vec<SlotSummary>,
+        selected: usize,
+        /// The name being typed for a new slot, while the player is naming one.
+        naming: Option<String>,
+    },
+    Hidden,
+}
@@ -1,2 +1,4 @@
 pub mod building;
+pub mod finances;
+pub mod load;
 pub mod sidebar;
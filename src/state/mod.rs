@@ -1,3 +1,7 @@
 pub mod board;
+pub mod calendar;
+pub mod company;
 pub mod game;
+pub mod history;
 pub mod menu;
+pub mod terrain;
@@ -0,0 +1,108 @@
+use super::board::{Board, StructureId, StructurePlacement, Vertex, VertexHeight};
+use super::company::Money;
+use std::fmt;
+
+/// What it costs to move one vertex by one height unit.
+pub const COST_PER_HEIGHT_CHANGE: Money = 500;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum TerrainError {
+    EdgeVertex {
+        vertex: Vertex,
+    },
+    MinHeight {
+        vertex: Vertex,
+    },
+    MaxHeight {
+        vertex: Vertex,
+    },
+    StructureInTheWay {
+        vertex: Vertex,
+        structure: StructureId,
+    },
+}
+
+impl fmt::Display for TerrainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TerrainError::EdgeVertex { vertex } => {
+                write!(f, "vertex {:?} is on the edge of the board", vertex)
+            }
+            TerrainError::MinHeight { vertex } => {
+                write!(f, "vertex {:?} is already at the minimum height", vertex)
+            }
+            TerrainError::MaxHeight { vertex } => {
+                write!(f, "vertex {:?} is already at the maximum height", vertex)
+            }
+            TerrainError::StructureInTheWay { vertex, .. } => write!(
+                f,
+                "vertex {:?} is underneath a structure; bulldoze to clear it",
+                vertex
+            ),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct VertexChange {
+    pub vertex: Vertex,
+    pub from: VertexHeight,
+    pub to: VertexHeight,
+}
+
+/// What a terrain operation does about structures standing on the vertices it needs to move.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum StructurePolicy {
+    /// Refuse the whole operation.
+    Protect,
+    /// Demolish the structures in the way.
+    Bulldoze,
+}
+
+/// The complete set of vertex changes needed to carry out a terrain operation, along with any
+/// structures which have to be demolished first.
+/// A plan is only produced once every change in it has been checked, so applying it cannot fail.
+#[derive(PartialEq, Clone, Debug)]
+pub struct TerrainPlan {
+    changes: Vec<VertexChange>,
+    demolitions: Vec<StructureId>,
+}
+
+impl TerrainPlan {
+    /// Wraps changes and demolitions which have already been checked against the board.
+    pub fn new(changes: Vec<VertexChange>, demolitions: Vec<StructureId>) -> Self {
+        Self {
+            changes,
+            demolitions,
+        }
+    }
+
+    pub fn changes(&self) -> &[VertexChange] {
+        &self.changes
+    }
+
+    /// What the whole plan costs, cascade included.
+    pub fn cost(&self) -> Money {
+        self.changes
+            .iter()
+            .map(|change| {
+                (change.to as Money - change.from as Money).abs() * COST_PER_HEIGHT_CHANGE
+            })
+            .sum()
+    }
+
+    /// Carries out the plan, returning the structures which were bulldozed to make way for it.
+    pub fn apply(&self, board: &mut Board) -> Vec<StructurePlacement> {
+        let removed = self
+            .demolitions
+            .iter()
+            .filter_map(|&id| board.demolish(id))
+            .collect();
+
+        for change in self.changes.iter() {
+            board.set_vertex_height(change.vertex, change.to);
+        }
+
+        removed
+    }
+}
@@ -0,0 +1,120 @@
+//! Little-endian reading and writing of the primitive values the save format is built from.
+
+use super::save::SaveError;
+
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn put_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn put_bool(&mut self, value: bool) {
+        self.put_u8(value as u8);
+    }
+
+    pub fn put_u16(&mut self, value: u16) {
+        self.put_bytes(&value.to_le_bytes());
+    }
+
+    pub fn put_u32(&mut self, value: u32) {
+        self.put_bytes(&value.to_le_bytes());
+    }
+
+    pub fn put_u64(&mut self, value: u64) {
+        self.put_bytes(&value.to_le_bytes());
+    }
+
+    pub fn put_i64(&mut self, value: i64) {
+        self.put_bytes(&value.to_le_bytes());
+    }
+
+    /// Written as a one byte length followed by the UTF-8 bytes, so at most 255 bytes are kept.
+    pub fn put_str(&mut self, value: &str) {
+        let mut len = value.len().min(u8::MAX as usize);
+        while !value.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.put_u8(len as u8);
+        self.put_bytes(&value.as_bytes()[..len]);
+    }
+}
+
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn get_bytes(&mut self, count: usize) -> Result<&'a [u8], SaveError> {
+        if count > self.bytes.len() {
+            return Err(SaveError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub fn get_u8(&mut self) -> Result<u8, SaveError> {
+        Ok(self.get_bytes(1)?[0])
+    }
+
+    pub fn get_bool(&mut self) -> Result<bool, SaveError> {
+        match self.get_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(SaveError::Corrupt(format!("{} is not a flag", other))),
+        }
+    }
+
+    pub fn get_u16(&mut self) -> Result<u16, SaveError> {
+        let mut raw = [0; 2];
+        raw.copy_from_slice(self.get_bytes(2)?);
+        Ok(u16::from_le_bytes(raw))
+    }
+
+    pub fn get_u32(&mut self) -> Result<u32, SaveError> {
+        let mut raw = [0; 4];
+        raw.copy_from_slice(self.get_bytes(4)?);
+        Ok(u32::from_le_bytes(raw))
+    }
+
+    pub fn get_u64(&mut self) -> Result<u64, SaveError> {
+        let mut raw = [0; 8];
+        raw.copy_from_slice(self.get_bytes(8)?);
+        Ok(u64::from_le_bytes(raw))
+    }
+
+    pub fn get_i64(&mut self) -> Result<i64, SaveError> {
+        let mut raw = [0; 8];
+        raw.copy_from_slice(self.get_bytes(8)?);
+        Ok(i64::from_le_bytes(raw))
+    }
+
+    pub fn get_str(&mut self) -> Result<String, SaveError> {
+        let len = self.get_u8()? as usize;
+        String::from_utf8(self.get_bytes(len)?.to_vec())
+            .map_err(|_| SaveError::Corrupt(String::from("text is not valid UTF-8")))
+    }
+}
@@ -0,0 +1,112 @@
+//! Grayscale PNG heightmaps. Reading and writing the images needs SDL, but the mapping between
+//! pixel values and vertex heights does not.
+
+#[cfg(feature = "sdl")]
+use crate::state::board::{Board, Vertex, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use crate::state::board::{VertexHeight, MAX_HEIGHT, MIN_HEIGHT};
+#[cfg(feature = "sdl")]
+use crate::systems::terrain::limit_slopes;
+#[cfg(feature = "sdl")]
+use sdl2::image::{LoadSurface, SaveSurface};
+#[cfg(feature = "sdl")]
+use sdl2::pixels::PixelFormatEnum;
+#[cfg(feature = "sdl")]
+use sdl2::surface::Surface;
+#[cfg(feature = "sdl")]
+use std::path::Path;
+
+/// Bytes per pixel in PixelFormatEnum::RGB24.
+#[cfg(feature = "sdl")]
+const BYTES_PER_PIXEL: usize = 3;
+
+/// Builds a board from a grayscale image with one pixel per vertex, so an image of
+/// (width + 1) x (height + 1) pixels gives a board of width x height blocks.
+/// Black is the lowest height and white the highest. Any slope steeper than the terrain rules
+/// allow is levelled off.
+#[cfg(feature = "sdl")]
+pub fn load_heightmap<P: AsRef<Path>>(path: P) -> Result<Board, String> {
+    let image = Surface::from_file(path)?;
+    let (image_width, image_height) = image.size();
+    let min_pixels = MIN_BOARD_SIZE + 1;
+    let max_pixels = MAX_BOARD_SIZE + 1;
+    if image_width < min_pixels
+        || image_height < min_pixels
+        || image_width > max_pixels
+        || image_height > max_pixels
+    {
+        return Err(format!(
+            "heightmap is {}x{} pixels but must be between {}x{} and {}x{}",
+            image_width, image_height, min_pixels, min_pixels, max_pixels, max_pixels
+        ));
+    }
+
+    // Whatever format the image was saved in, copy it onto a surface with a known layout.
+    let mut pixels = Surface::new(image_width, image_height, PixelFormatEnum::RGB24)?;
+    image.blit(None, &mut pixels, None)?;
+
+    let mut board = Board::new(image_width - 1, image_height - 1);
+    let pitch = pixels.pitch() as usize;
+    pixels.with_lock(|data| {
+        for y in 0..image_height {
+            for x in 0..image_width {
+                let offset = y as usize * pitch + x as usize * BYTES_PER_PIXEL;
+                let rgb = &data[offset..offset + BYTES_PER_PIXEL];
+                let gray = (rgb[0] as u32 + rgb[1] as u32 + rgb[2] as u32) / 3;
+                board.set_vertex_height(Vertex { x, y }, quantise(gray as u8));
+            }
+        }
+    });
+
+    limit_slopes(&mut board);
+
+    Ok(board)
+}
+
+/// Writes the board's vertex heights to a grayscale PNG which `load_heightmap` reads back into
+/// the same heights.
+#[cfg(feature = "sdl")]
+pub fn save_heightmap<P: AsRef<Path>>(board: &Board, path: P) -> Result<(), String> {
+    let image_width = board.width() + 1;
+    let image_height = board.height() + 1;
+    let mut pixels = Surface::new(image_width, image_height, PixelFormatEnum::RGB24)?;
+    let pitch = pixels.pitch() as usize;
+    pixels.with_lock_mut(|data| {
+        for y in 0..image_height {
+            for x in 0..image_width {
+                let offset = y as usize * pitch + x as usize * BYTES_PER_PIXEL;
+                let gray = pixel_value(board.vertex_height(Vertex { x, y }));
+                for channel in data[offset..offset + BYTES_PER_PIXEL].iter_mut() {
+                    *channel = gray;
+                }
+            }
+        }
+    });
+
+    pixels.save(path)
+}
+
+/// Maps a pixel value onto the nearest allowed vertex height.
+pub fn quantise(gray: u8) -> VertexHeight {
+    let range = (MAX_HEIGHT - MIN_HEIGHT) as u32;
+    MIN_HEIGHT + ((gray as u32 * range + 127) / 255) as VertexHeight
+}
+
+/// Spreads the allowed vertex heights evenly across the full range of pixel values.
+pub fn pixel_value(height: VertexHeight) -> u8 {
+    let range = (MAX_HEIGHT - MIN_HEIGHT) as u32;
+    ((height.min(MAX_HEIGHT) - MIN_HEIGHT) as u32 * 255 / range) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_height_survives_a_trip_through_a_pixel() {
+        for height in MIN_HEIGHT..=MAX_HEIGHT {
+            assert_eq!(quantise(pixel_value(height)), height);
+        }
+        assert_eq!(pixel_value(MIN_HEIGHT), 0);
+        assert_eq!(pixel_value(MAX_HEIGHT), 255);
+    }
+}
@@ -0,0 +1,6 @@
+mod encoding;
+pub mod heightmap;
+pub mod recording;
+pub mod save;
+pub mod slots;
+pub mod text_map;
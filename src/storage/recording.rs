@@ -0,0 +1,546 @@
+//! Recordings of every action applied during a session, so the session can be replayed exactly.
+//!
+//! A recording starts with a save of the game as it was when recording began. After that come
+//! entries, each stamped with the simulation tick it happened on: either an action, or a save of
+//! the whole game when one was loaded from disk part way through.
+
+use super::encoding::{Decoder, Encoder};
+use super::save::{self, get_block, put_block, SaveError};
+use crate::action::GameAction;
+use crate::state::calendar::GameSpeed;
+use crate::state::game::GameState;
+use crate::state::menu::building::{Building, BuyBuildingButton, Category};
+use crate::state::menu::sidebar::SidebarMenu;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Every recording starts with these bytes.
+const MAGIC: &[u8; 4] = b"TREC";
+pub const RECORDING_VERSION: u16 = 1;
+
+pub struct Recording {
+    /// The game when recording began, as a save.
+    pub start: Vec<u8>,
+    pub entries: Vec<RecordedEntry>,
+}
+
+#[derive(PartialEq, Clone)]
+pub struct RecordedEntry {
+    pub tick: u64,
+    pub event: RecordedEvent,
+}
+
+#[derive(PartialEq, Clone)]
+pub enum RecordedEvent {
+    Action(GameAction),
+    /// The whole game, as a save, replacing the one being played.
+    Snapshot(Vec<u8>),
+}
+
+impl Recording {
+    /// The game as it was when recording began.
+    pub fn start_game(&self) -> Result<GameState, SaveError> {
+        save::decode_game(&self.start)
+    }
+}
+
+pub fn load_recording<P: AsRef<Path>>(path: P) -> Result<Recording, SaveError> {
+    decode_recording(&fs::read(path)?)
+}
+
+pub fn decode_recording(bytes: &[u8]) -> Result<Recording, SaveError> {
+    let mut input = Decoder::new(bytes);
+    if input
+        .get_bytes(MAGIC.len())
+        .map_err(|_| SaveError::NotASave)?
+        != MAGIC
+    {
+        return Err(SaveError::NotASave);
+    }
+    let version = input.get_u16()?;
+    if version != RECORDING_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+    let start = get_snapshot(&mut input)?;
+
+    let mut entries = Vec::new();
+    while input.remaining() > 0 {
+        let tick = input.get_u64()?;
+        let event = match input.get_u8()? {
+            0 => RecordedEvent::Action(get_action(&mut input)?),
+            1 => RecordedEvent::Snapshot(get_snapshot(&mut input)?),
+            other => {
+                return Err(SaveError::Corrupt(format!(
+                    "unknown recording entry {}",
+                    other
+                )))
+            }
+        };
+        if entries
+            .last()
+            .is_some_and(|last: &RecordedEntry| last.tick > tick)
+        {
+            return Err(SaveError::Corrupt(String::from(
+                "recording entries are out of order",
+            )));
+        }
+        entries.push(RecordedEntry { tick, event });
+    }
+
+    Ok(Recording { start, entries })
+}
+
+/// Writes a recording as the session goes. Every entry is flushed as soon as it's recorded, so a
+/// recording written to disk keeps everything up to a crash.
+pub struct Recorder<W: Write> {
+    out: W,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P, game: &GameState) -> Result<Self, SaveError> {
+        Self::new(BufWriter::new(File::create(path)?), game)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(out: W, game: &GameState) -> Result<Self, SaveError> {
+        let mut recorder = Self { out };
+        let mut start = Encoder::new();
+        put_start(&mut start, &save::encode_game(game));
+        recorder.write(start)?;
+        Ok(recorder)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    pub fn record_action(&mut self, tick: u64, action: GameAction) -> Result<(), SaveError> {
+        self.record(RecordedEntry {
+            tick,
+            event: RecordedEvent::Action(action),
+        })
+    }
+
+    pub fn record_snapshot(&mut self, tick: u64, game: &GameState) -> Result<(), SaveError> {
+        self.record(RecordedEntry {
+            tick,
+            event: RecordedEvent::Snapshot(save::encode_game(game)),
+        })
+    }
+
+    fn record(&mut self, entry: RecordedEntry) -> Result<(), SaveError> {
+        let mut out = Encoder::new();
+        put_entry(&mut out, &entry);
+        self.write(out)
+    }
+
+    fn write(&mut self, bytes: Encoder) -> Result<(), SaveError> {
+        self.out.write_all(&bytes.into_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+fn put_start(out: &mut Encoder, start: &[u8]) {
+    out.put_bytes(MAGIC);
+    out.put_u16(RECORDING_VERSION);
+    put_snapshot(out, start);
+}
+
+fn put_entry(out: &mut Encoder, entry: &RecordedEntry) {
+    out.put_u64(entry.tick);
+    match &entry.event {
+        RecordedEvent::Action(action) => {
+            out.put_u8(0);
+            put_action(out, *action);
+        }
+        RecordedEvent::Snapshot(bytes) => {
+            out.put_u8(1);
+            put_snapshot(out, bytes);
+        }
+    }
+}
+
+fn put_snapshot(out: &mut Encoder, bytes: &[u8]) {
+    out.put_u32(bytes.len() as u32);
+    out.put_bytes(bytes);
+}
+
+/// Reads a save embedded in the recording, checking that it loads.
+fn get_snapshot(input: &mut Decoder) -> Result<Vec<u8>, SaveError> {
+    let len = input.get_u32()? as usize;
+    let bytes = input.get_bytes(len)?.to_vec();
+    save::decode_game(&bytes)?;
+    Ok(bytes)
+}
+
+fn put_action(out: &mut Encoder, action: GameAction) {
+    match action {
+        GameAction::Hover { block } => {
+            out.put_u8(0);
+            out.put_bool(block.is_some());
+            if let Some(block) = block {
+                put_block(out, block);
+            }
+        }
+        GameAction::SidebarHover { button } => {
+            out.put_u8(1);
+            out.put_bool(button.is_some());
+            if let Some(button) = button {
+                put_menu(out, button);
+            }
+        }
+        GameAction::OpenMenu { menu } => {
+            out.put_u8(2);
+            put_menu(out, menu);
+        }
+        GameAction::PlaceStructure => out.put_u8(3),
+        GameAction::ConfirmFlatten => out.put_u8(4),
+        GameAction::Demolish => out.put_u8(5),
+        GameAction::Focus => out.put_u8(6),
+        GameAction::RaiseTerrain => out.put_u8(7),
+        GameAction::SelectTerraform => out.put_u8(8),
+        GameAction::SelectFlatten => out.put_u8(9),
+        GameAction::Flatten => out.put_u8(10),
+        GameAction::CancelFlatten => out.put_u8(11),
+        GameAction::AdjustFlattenLevel { delta } => {
+            out.put_u8(12);
+            out.put_u8(delta as u8);
+        }
+        GameAction::RotateStructure => out.put_u8(13),
+        GameAction::LowerTerrain => out.put_u8(14),
+        GameAction::ResizeBrush { delta } => {
+            out.put_u8(15);
+            out.put_u8(delta as u8);
+        }
+        GameAction::CycleBrushShape => out.put_u8(16),
+        GameAction::ToggleBrushFalloff => out.put_u8(17),
+        GameAction::ToggleBulldoze => out.put_u8(18),
+        GameAction::ExportHeightmap => out.put_u8(19),
+        GameAction::ExportMap => out.put_u8(20),
+        GameAction::SaveGame => out.put_u8(21),
+        GameAction::LoadGame => out.put_u8(22),
+        GameAction::SelectSlot { delta } => {
+            out.put_u8(23);
+            out.put_u8(delta as u8);
+        }
+        GameAction::CloseLoadScreen => out.put_u8(24),
+        GameAction::Undo => out.put_u8(25),
+        GameAction::Redo => out.put_u8(26),
+        GameAction::Quit => out.put_u8(27),
+        GameAction::TogglePause => out.put_u8(28),
+        GameAction::CloseFinancesScreen => out.put_u8(30),
+        GameAction::Borrow => out.put_u8(31),
+        GameAction::RepayLoan => out.put_u8(32),
+        GameAction::BuyBuildingHover { button } => {
+            out.put_u8(33);
+            out.put_bool(button.is_some());
+            if let Some(button) = button {
+                put_buy_building_button(out, button);
+            }
+        }
+        GameAction::SelectBuildingCategory { category } => {
+            out.put_u8(34);
+            put_category(out, category);
+        }
+        GameAction::SelectBuilding { building } => {
+            out.put_u8(35);
+            put_building(out, building);
+        }
+        GameAction::BuyBuilding => out.put_u8(36),
+        GameAction::CloseBuyBuildingScreen => out.put_u8(37),
+        GameAction::OpenLoadScreen => out.put_u8(38),
+        GameAction::NameSlot => out.put_u8(39),
+        GameAction::TypeSlotName { character } => {
+            out.put_u8(40);
+            out.put_u32(character as u32);
+        }
+        GameAction::EraseSlotName => out.put_u8(41),
+        GameAction::CancelSlotName => out.put_u8(42),
+        GameAction::SetSpeed { speed } => {
+            out.put_u8(29);
+            put_speed(out, speed);
+        }
+    }
+}
+
+fn get_action(input: &mut Decoder) -> Result<GameAction, SaveError> {
+    let action = match input.get_u8()? {
+        0 => GameAction::Hover {
+            block: if input.get_bool()? {
+                Some(get_block(input)?)
+            } else {
+                None
+            },
+        },
+        1 => GameAction::SidebarHover {
+            button: if input.get_bool()? {
+                Some(get_menu(input)?)
+            } else {
+                None
+            },
+        },
+        2 => GameAction::OpenMenu {
+            menu: get_menu(input)?,
+        },
+        3 => GameAction::PlaceStructure,
+        4 => GameAction::ConfirmFlatten,
+        5 => GameAction::Demolish,
+        6 => GameAction::Focus,
+        7 => GameAction::RaiseTerrain,
+        8 => GameAction::SelectTerraform,
+        9 => GameAction::SelectFlatten,
+        10 => GameAction::Flatten,
+        11 => GameAction::CancelFlatten,
+        12 => GameAction::AdjustFlattenLevel {
+            delta: input.get_u8()? as i8,
+        },
+        13 => GameAction::RotateStructure,
+        14 => GameAction::LowerTerrain,
+        15 => GameAction::ResizeBrush {
+            delta: input.get_u8()? as i8,
+        },
+        16 => GameAction::CycleBrushShape,
+        17 => GameAction::ToggleBrushFalloff,
+        18 => GameAction::ToggleBulldoze,
+        19 => GameAction::ExportHeightmap,
+        20 => GameAction::ExportMap,
+        21 => GameAction::SaveGame,
+        22 => GameAction::LoadGame,
+        23 => GameAction::SelectSlot {
+            delta: input.get_u8()? as i8,
+        },
+        24 => GameAction::CloseLoadScreen,
+        25 => GameAction::Undo,
+        26 => GameAction::Redo,
+        27 => GameAction::Quit,
+        28 => GameAction::TogglePause,
+        29 => GameAction::SetSpeed {
+            speed: get_speed(input)?,
+        },
+        30 => GameAction::CloseFinancesScreen,
+        31 => GameAction::Borrow,
+        32 => GameAction::RepayLoan,
+        33 => GameAction::BuyBuildingHover {
+            button: if input.get_bool()? {
+                Some(get_buy_building_button(input)?)
+            } else {
+                None
+            },
+        },
+        34 => GameAction::SelectBuildingCategory {
+            category: get_category(input)?,
+        },
+        35 => GameAction::SelectBuilding {
+            building: get_building(input)?,
+        },
+        36 => GameAction::BuyBuilding,
+        37 => GameAction::CloseBuyBuildingScreen,
+        38 => GameAction::OpenLoadScreen,
+        39 => GameAction::NameSlot,
+        40 => {
+            let code = input.get_u32()?;
+            let character = char::from_u32(code)
+                .ok_or_else(|| SaveError::Corrupt(format!("unknown character {}", code)))?;
+            GameAction::TypeSlotName { character }
+        }
+        41 => GameAction::EraseSlotName,
+        42 => GameAction::CancelSlotName,
+        other => return Err(SaveError::Corrupt(format!("unknown action {}", other))),
+    };
+    Ok(action)
+}
+
+fn put_speed(out: &mut Encoder, speed: GameSpeed) {
+    let tag = match speed {
+        GameSpeed::X1 => 0,
+        GameSpeed::X2 => 1,
+        GameSpeed::X4 => 2,
+        GameSpeed::X8 => 3,
+    };
+    out.put_u8(tag);
+}
+
+fn get_speed(input: &mut Decoder) -> Result<GameSpeed, SaveError> {
+    let speed = match input.get_u8()? {
+        0 => GameSpeed::X1,
+        1 => GameSpeed::X2,
+        2 => GameSpeed::X4,
+        3 => GameSpeed::X8,
+        other => return Err(SaveError::Corrupt(format!("unknown speed {}", other))),
+    };
+    Ok(speed)
+}
+
+fn put_menu(out: &mut Encoder, menu: SidebarMenu) {
+    let tag = match menu {
+        SidebarMenu::Close => 0,
+        SidebarMenu::Save => 1,
+        SidebarMenu::Music => 2,
+        SidebarMenu::Graphics => 3,
+        SidebarMenu::Help => 4,
+        SidebarMenu::Rotation => 5,
+        SidebarMenu::Metrics => 6,
+        SidebarMenu::Finances => 7,
+        SidebarMenu::News => 8,
+        SidebarMenu::Info => 9,
+        SidebarMenu::Navigation => 10,
+        SidebarMenu::Building => 11,
+        SidebarMenu::Rail => 12,
+        SidebarMenu::Demolish => 13,
+        SidebarMenu::Point => 14,
+    };
+    out.put_u8(tag);
+}
+
+fn get_menu(input: &mut Decoder) -> Result<SidebarMenu, SaveError> {
+    let menu = match input.get_u8()? {
+        0 => SidebarMenu::Close,
+        1 => SidebarMenu::Save,
+        2 => SidebarMenu::Music,
+        3 => SidebarMenu::Graphics,
+        4 => SidebarMenu::Help,
+        5 => SidebarMenu::Rotation,
+        6 => SidebarMenu::Metrics,
+        7 => SidebarMenu::Finances,
+        8 => SidebarMenu::News,
+        9 => SidebarMenu::Info,
+        10 => SidebarMenu::Navigation,
+        11 => SidebarMenu::Building,
+        12 => SidebarMenu::Rail,
+        13 => SidebarMenu::Demolish,
+        14 => SidebarMenu::Point,
+        other => return Err(SaveError::Corrupt(format!("unknown menu {}", other))),
+    };
+    Ok(menu)
+}
+
+fn put_category(out: &mut Encoder, category: Category) {
+    let tag = match category {
+        Category::Terminals => 0,
+        Category::Production => 1,
+        Category::Retail => 2,
+        Category::CityBuildings => 3,
+    };
+    out.put_u8(tag);
+}
+
+fn get_category(input: &mut Decoder) -> Result<Category, SaveError> {
+    let category = match input.get_u8()? {
+        0 => Category::Terminals,
+        1 => Category::Production,
+        2 => Category::Retail,
+        3 => Category::CityBuildings,
+        other => {
+            return Err(SaveError::Corrupt(format!(
+                "unknown building category {}",
+                other
+            )))
+        }
+    };
+    Ok(category)
+}
+
+fn put_building(out: &mut Encoder, building: Building) {
+    let tag = match building {
+        Building::TruckDepot => 0,
+        Building::TrainStation => 1,
+        Building::Airport => 2,
+        Building::Harbor => 3,
+        Building::AutomobileFactory => 4,
+        Building::Woodshop => 5,
+        Building::ElectronicsFactory => 6,
+        Building::SportsEquipmentFactory => 7,
+        Building::ToyFactory => 8,
+        Building::JewelryFactory => 9,
+        Building::Warehouse => 10,
+        Building::BuildingEquipmentFactory => 11,
+        Building::PaperFactory => 12,
+        Building::PrintingPress => 13,
+        Building::ToyStore => 14,
+        Building::SportingGoodsStore => 15,
+        Building::FurnitureStore => 16,
+        Building::Jeweler => 17,
+        Building::ElectronicsStore => 18,
+        Building::CarDealership => 19,
+        Building::BuildingEquipmentStore => 20,
+        Building::StationaryStore => 21,
+        Building::CulturalCenter => 22,
+        Building::TennisCourt => 23,
+        Building::SwimmingPool => 24,
+        Building::SportsStadium => 25,
+        Building::RaceTrack => 26,
+        Building::University => 27,
+        Building::AmusementPark => 28,
+    };
+    out.put_u8(tag);
+}
+
+fn get_building(input: &mut Decoder) -> Result<Building, SaveError> {
+    let building = match input.get_u8()? {
+        0 => Building::TruckDepot,
+        1 => Building::TrainStation,
+        2 => Building::Airport,
+        3 => Building::Harbor,
+        4 => Building::AutomobileFactory,
+        5 => Building::Woodshop,
+        6 => Building::ElectronicsFactory,
+        7 => Building::SportsEquipmentFactory,
+        8 => Building::ToyFactory,
+        9 => Building::JewelryFactory,
+        10 => Building::Warehouse,
+        11 => Building::BuildingEquipmentFactory,
+        12 => Building::PaperFactory,
+        13 => Building::PrintingPress,
+        14 => Building::ToyStore,
+        15 => Building::SportingGoodsStore,
+        16 => Building::FurnitureStore,
+        17 => Building::Jeweler,
+        18 => Building::ElectronicsStore,
+        19 => Building::CarDealership,
+        20 => Building::BuildingEquipmentStore,
+        21 => Building::StationaryStore,
+        22 => Building::CulturalCenter,
+        23 => Building::TennisCourt,
+        24 => Building::SwimmingPool,
+        25 => Building::SportsStadium,
+        26 => Building::RaceTrack,
+        27 => Building::University,
+        28 => Building::AmusementPark,
+        other => return Err(SaveError::Corrupt(format!("unknown building {}", other))),
+    };
+    Ok(building)
+}
+
+fn put_buy_building_button(out: &mut Encoder, button: BuyBuildingButton) {
+    match button {
+        BuyBuildingButton::Category(category) => {
+            out.put_u8(0);
+            put_category(out, category);
+        }
+        BuyBuildingButton::Building(building) => {
+            out.put_u8(1);
+            put_building(out, building);
+        }
+        BuyBuildingButton::Buy => out.put_u8(2),
+        BuyBuildingButton::Close => out.put_u8(3),
+    }
+}
+
+fn get_buy_building_button(input: &mut Decoder) -> Result<BuyBuildingButton, SaveError> {
+    let button = match input.get_u8()? {
+        0 => BuyBuildingButton::Category(get_category(input)?),
+        1 => BuyBuildingButton::Building(get_building(input)?),
+        2 => BuyBuildingButton::Buy,
+        3 => BuyBuildingButton::Close,
+        other => {
+            return Err(SaveError::Corrupt(format!(
+                "unknown buy building button {}",
+                other
+            )))
+        }
+    };
+    Ok(button)
+}
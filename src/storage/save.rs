@@ -0,0 +1,889 @@
+use super::encoding::{Decoder, Encoder};
+use crate::state::board::{
+    Block, Board, Direction, Mineral, Structure, StructurePlacement, Vertex, MAX_BOARD_SIZE,
+    MAX_HEIGHT,
+};
+use crate::state::calendar::{GameDate, START_DATE};
+use crate::state::company::{
+    Ledger, LedgerTotals, Money, DEFAULT_DEMOLITION_REFUND, LEDGER_CATEGORIES, LEDGER_MONTHS,
+    LEDGER_YEARS, STARTING_CASH,
+};
+use crate::state::game::{
+    Brush, BrushFalloff, BrushShape, FlattenLevel, GameState, PlayerMode, MAX_BRUSH_RADIUS,
+};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Every save file starts with these bytes.
+const MAGIC: &[u8; 4] = b"TITN";
+/// The version of the format written by `encode_game`. Bump this whenever the layout changes and
+/// add a step to `MIGRATIONS` that upgrades the previous version.
+pub const SAVE_VERSION: u16 = 5;
+/// The most bytes the header and metadata can take up in a save of any version, so listing saves
+/// never needs to read further into the file than this.
+const MAX_METADATA_LEN: u64 = 6 + 1 + u8::MAX as u64 + 4 + 8 + 4;
+
+/// Upgrades the body of a save, everything after the header, by one version. The step at index
+/// `i` takes a body written as version `i + 1` and returns it as version `i + 2`.
+type Migration = fn(&[u8]) -> Result<Vec<u8>, SaveError>;
+
+const MIGRATIONS: [Migration; SAVE_VERSION as usize - 1] = [
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+];
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The file isn't a save file at all.
+    NotASave,
+    UnsupportedVersion(u16),
+    Truncated,
+    Corrupt(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::NotASave => write!(f, "not a save file"),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "save file version {} is not supported (this game reads up to version {})",
+                version, SAVE_VERSION
+            ),
+            SaveError::Truncated => write!(f, "save file ends unexpectedly"),
+            SaveError::Corrupt(reason) => write!(f, "save file is corrupt: {}", reason),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+/// Writes the game to disk. The file is written alongside the destination first and then moved
+/// into place, so a failed save never leaves a half written file behind.
+pub fn save_game<P: AsRef<Path>>(game: &GameState, path: P) -> Result<(), SaveError> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let partial = path.with_extension("partial");
+    fs::write(&partial, encode_game(game))?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+pub fn load_game<P: AsRef<Path>>(path: P) -> Result<GameState, SaveError> {
+    decode_game(&fs::read(path)?)
+}
+
+/// Reads just enough of a save to describe it, without loading the board.
+pub fn load_metadata<P: AsRef<Path>>(path: P) -> Result<SaveMetadata, SaveError> {
+    let mut bytes = Vec::new();
+    File::open(path)?
+        .take(MAX_METADATA_LEN)
+        .read_to_end(&mut bytes)?;
+    decode_metadata(&bytes)
+}
+
+/// What the player sees about a save before loading it. Written at the start of every save.
+#[derive(PartialEq, Clone, Debug)]
+pub struct SaveMetadata {
+    /// The slot manual saves of the game go into.
+    pub name: String,
+    pub date: GameDate,
+    pub cash: Money,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl SaveMetadata {
+    pub fn of(game: &GameState) -> Self {
+        Self {
+            name: game.save_name.clone(),
+            date: game.date,
+            cash: game.company.cash,
+            width: game.board.width(),
+            height: game.board.height(),
+        }
+    }
+}
+
+fn put_metadata(out: &mut Encoder, metadata: &SaveMetadata) {
+    out.put_str(&metadata.name);
+    out.put_u16(metadata.date.year);
+    out.put_u8(metadata.date.month);
+    out.put_u8(metadata.date.day);
+    out.put_i64(metadata.cash);
+    // Boards are never wider or taller than MAX_BOARD_SIZE, so the dimensions fit in 16 bits.
+    out.put_u16(metadata.width as u16);
+    out.put_u16(metadata.height as u16);
+}
+
+fn get_metadata(input: &mut Decoder) -> Result<SaveMetadata, SaveError> {
+    let name = input.get_str()?;
+    let date = GameDate {
+        year: input.get_u16()?,
+        month: input.get_u8()?,
+        day: input.get_u8()?,
+    };
+    if date.month < 1 || date.month > 12 || date.day < 1 || date.day > 31 {
+        return Err(SaveError::Corrupt(format!(
+            "{}-{}-{} is not a date",
+            date.year, date.month, date.day
+        )));
+    }
+    let cash = input.get_i64()?;
+    let width = input.get_u16()? as u32;
+    let height = input.get_u16()? as u32;
+    // Only generated boards are held to MIN_BOARD_SIZE. Text maps and heightmaps can be smaller.
+    let sizes = 1..=MAX_BOARD_SIZE;
+    if !sizes.contains(&width) || !sizes.contains(&height) {
+        return Err(SaveError::Corrupt(format!(
+            "board size {}x{} is out of range",
+            width, height
+        )));
+    }
+    Ok(SaveMetadata {
+        name,
+        date,
+        cash,
+        width,
+        height,
+    })
+}
+
+/// The part of a save file which is read before anything else and never changes layout.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SaveHeader {
+    pub version: u16,
+}
+
+fn put_header(out: &mut Encoder, header: SaveHeader) {
+    out.put_bytes(MAGIC);
+    out.put_u16(header.version);
+}
+
+fn get_header(input: &mut Decoder) -> Result<SaveHeader, SaveError> {
+    if input
+        .get_bytes(MAGIC.len())
+        .map_err(|_| SaveError::NotASave)?
+        != MAGIC
+    {
+        return Err(SaveError::NotASave);
+    }
+    let version = input.get_u16()?;
+    if version == 0 || version > SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+    Ok(SaveHeader { version })
+}
+
+/// Runs the body of a save through every migration between its version and the current one.
+fn migrate(version: u16, body: &[u8]) -> Result<Vec<u8>, SaveError> {
+    let mut body = body.to_vec();
+    for step in &MIGRATIONS[version as usize - 1..] {
+        body = step(&body)?;
+    }
+    Ok(body)
+}
+
+/// Version 2 started the body with metadata: the save name, game date and company cash, followed
+/// by the board dimensions. Older games are given an empty name and the starting date and cash.
+fn migrate_v1_to_v2(body: &[u8]) -> Result<Vec<u8>, SaveError> {
+    let mut input = Decoder::new(body);
+    let width = input.get_u16()?;
+    let height = input.get_u16()?;
+    let mut out = Encoder::new();
+    out.put_str("");
+    out.put_u16(START_DATE.year);
+    out.put_u8(START_DATE.month);
+    out.put_u8(START_DATE.day);
+    out.put_i64(STARTING_CASH);
+    out.put_u16(width);
+    out.put_u16(height);
+    let rest = input.remaining();
+    out.put_bytes(input.get_bytes(rest)?);
+    Ok(out.into_bytes())
+}
+
+/// Version 3 added the company ledger after the rest of the game. Older games start with an empty
+/// one: no income or expenses this month or this year, and no months or years gone by.
+fn migrate_v2_to_v3(body: &[u8]) -> Result<Vec<u8>, SaveError> {
+    let mut out = Encoder::new();
+    out.put_bytes(body);
+    for _ in 0..2 * 4 * 2 {
+        out.put_i64(0);
+    }
+    out.put_u16(0);
+    out.put_u16(0);
+    Ok(out.into_bytes())
+}
+
+/// Version 4 added the percentage of a structure's cost refunded on demolition after the ledger.
+/// Older games get the default refund.
+fn migrate_v3_to_v4(body: &[u8]) -> Result<Vec<u8>, SaveError> {
+    let mut out = Encoder::new();
+    out.put_bytes(body);
+    out.put_u8(DEFAULT_DEMOLITION_REFUND);
+    Ok(out.into_bytes())
+}
+
+/// Version 5 added the company's loan, how many days its cash has been below zero and whether it
+/// has gone bankrupt after the demolition refund. Older games owe nothing and are solvent.
+fn migrate_v4_to_v5(body: &[u8]) -> Result<Vec<u8>, SaveError> {
+    let mut out = Encoder::new();
+    out.put_bytes(body);
+    out.put_i64(0);
+    out.put_u16(0);
+    out.put_bool(false);
+    Ok(out.into_bytes())
+}
+
+pub fn encode_game(game: &GameState) -> Vec<u8> {
+    let mut out = Encoder::new();
+    put_header(
+        &mut out,
+        SaveHeader {
+            version: SAVE_VERSION,
+        },
+    );
+
+    put_metadata(&mut out, &SaveMetadata::of(game));
+
+    let board = &game.board;
+    out.put_bytes(board.vertex_heights());
+
+    let slots: Vec<_> = board.structure_slots().collect();
+    out.put_u32(slots.len() as u32);
+    for (generation, placement) in slots {
+        out.put_u32(generation);
+        out.put_bool(placement.is_some());
+        if let Some(placement) = placement {
+            put_structure(&mut out, placement.structure());
+            put_direction(&mut out, placement.orientation());
+            put_block(&mut out, placement.origin());
+        }
+    }
+    out.put_u32(board.free_slots().len() as u32);
+    for &idx in board.free_slots() {
+        out.put_u32(idx as u32);
+    }
+
+    out.put_u32(game.focal_point.x);
+    out.put_u32(game.focal_point.y);
+    put_player_mode(&mut out, game.player_mode);
+    out.put_bool(game.bulldoze);
+    put_ledger(&mut out, &game.company.ledger);
+    out.put_u8(game.demolition_refund);
+    out.put_i64(game.company.loan);
+    out.put_u16(game.company.days_in_debt);
+    out.put_bool(game.company.bankrupt);
+
+    out.into_bytes()
+}
+
+/// Reads the metadata from the start of a save, which may be cut off anywhere after it.
+pub fn decode_metadata(bytes: &[u8]) -> Result<SaveMetadata, SaveError> {
+    let mut input = Decoder::new(bytes);
+    let header = get_header(&mut input)?;
+    let rest = input.remaining();
+    let body = migrate(header.version, input.get_bytes(rest)?)?;
+    get_metadata(&mut Decoder::new(&body))
+}
+
+pub fn decode_game(bytes: &[u8]) -> Result<GameState, SaveError> {
+    let mut input = Decoder::new(bytes);
+    let header = get_header(&mut input)?;
+    let rest = input.remaining();
+    let body = migrate(header.version, input.get_bytes(rest)?)?;
+    let mut input = Decoder::new(&body);
+
+    let metadata = get_metadata(&mut input)?;
+    let (width, height) = (metadata.width, metadata.height);
+    let vertex_count = ((width + 1) * (height + 1)) as usize;
+    let vertices = input.get_bytes(vertex_count)?.to_vec();
+    if let Some(h) = vertices.iter().find(|&&h| h > MAX_HEIGHT) {
+        return Err(SaveError::Corrupt(format!(
+            "vertex height {} is too high",
+            h
+        )));
+    }
+
+    let slot_count = input.get_u32()?;
+    let mut slots = Vec::new();
+    for _ in 0..slot_count {
+        let generation = input.get_u32()?;
+        let placement = if input.get_bool()? {
+            let structure = get_structure(&mut input)?;
+            let orientation = get_direction(&mut input)?;
+            let origin = get_block(&mut input)?;
+            Some(StructurePlacement::new(structure, orientation, origin))
+        } else {
+            None
+        };
+        slots.push((generation, placement));
+    }
+    let free_count = input.get_u32()?;
+    let mut free_slots = Vec::new();
+    for _ in 0..free_count {
+        free_slots.push(input.get_u32()? as usize);
+    }
+    let board =
+        Board::restore(width, height, vertices, slots, free_slots).map_err(SaveError::Corrupt)?;
+
+    let focal_point = Vertex {
+        x: input.get_u32()?,
+        y: input.get_u32()?,
+    };
+    if focal_point.x > width || focal_point.y > height {
+        return Err(SaveError::Corrupt(String::from(
+            "focal point is off the board",
+        )));
+    }
+    let player_mode = get_player_mode(&mut input, &board)?;
+    let bulldoze = input.get_bool()?;
+    let ledger = get_ledger(&mut input)?;
+    let demolition_refund = input.get_u8()?;
+    if demolition_refund > 100 {
+        return Err(SaveError::Corrupt(format!(
+            "demolition refund of {}%",
+            demolition_refund
+        )));
+    }
+    let loan = input.get_i64()?;
+    if loan < 0 {
+        return Err(SaveError::Corrupt(format!("loan of {}", loan)));
+    }
+    let days_in_debt = input.get_u16()?;
+    let bankrupt = input.get_bool()?;
+
+    if input.remaining() > 0 {
+        return Err(SaveError::Corrupt(format!(
+            "{} unexpected bytes at the end",
+            input.remaining()
+        )));
+    }
+
+    let mut game = GameState::new(board);
+    game.save_name = metadata.name;
+    game.date = metadata.date;
+    game.company.cash = metadata.cash;
+    game.company.ledger = ledger;
+    game.company.loan = loan;
+    game.company.days_in_debt = days_in_debt;
+    game.company.bankrupt = bankrupt;
+    game.demolition_refund = demolition_refund;
+    game.focal_point = focal_point;
+    game.player_mode = player_mode;
+    game.bulldoze = bulldoze;
+    Ok(game)
+}
+
+fn put_ledger(out: &mut Encoder, ledger: &Ledger) {
+    put_ledger_totals(out, &ledger.this_month);
+    put_ledger_totals(out, &ledger.this_year);
+    out.put_u16(ledger.past_months.len() as u16);
+    for (year, month, totals) in ledger.past_months.iter() {
+        out.put_u16(*year);
+        out.put_u8(*month);
+        put_ledger_totals(out, totals);
+    }
+    out.put_u16(ledger.past_years.len() as u16);
+    for (year, totals) in ledger.past_years.iter() {
+        out.put_u16(*year);
+        put_ledger_totals(out, totals);
+    }
+}
+
+fn get_ledger(input: &mut Decoder) -> Result<Ledger, SaveError> {
+    let mut ledger = Ledger::new();
+    ledger.this_month = get_ledger_totals(input)?;
+    ledger.this_year = get_ledger_totals(input)?;
+
+    let month_count = input.get_u16()? as usize;
+    if month_count > LEDGER_MONTHS {
+        return Err(SaveError::Corrupt(format!(
+            "{} months in the ledger",
+            month_count
+        )));
+    }
+    for _ in 0..month_count {
+        let year = input.get_u16()?;
+        let month = input.get_u8()?;
+        if !(1..=12).contains(&month) {
+            return Err(SaveError::Corrupt(format!("ledger month {}", month)));
+        }
+        ledger
+            .past_months
+            .push_back((year, month, get_ledger_totals(input)?));
+    }
+
+    let year_count = input.get_u16()? as usize;
+    if year_count > LEDGER_YEARS {
+        return Err(SaveError::Corrupt(format!(
+            "{} years in the ledger",
+            year_count
+        )));
+    }
+    for _ in 0..year_count {
+        let year = input.get_u16()?;
+        ledger
+            .past_years
+            .push_back((year, get_ledger_totals(input)?));
+    }
+    Ok(ledger)
+}
+
+fn put_ledger_totals(out: &mut Encoder, totals: &LedgerTotals) {
+    for &category in LEDGER_CATEGORIES.iter() {
+        out.put_i64(totals.income(category));
+        out.put_i64(totals.expenses(category));
+    }
+}
+
+fn get_ledger_totals(input: &mut Decoder) -> Result<LedgerTotals, SaveError> {
+    let mut totals = LedgerTotals::new();
+    for &category in LEDGER_CATEGORIES.iter() {
+        totals.add_income(category, input.get_i64()?);
+        totals.add_expenses(category, input.get_i64()?);
+    }
+    Ok(totals)
+}
+
+pub(super) fn put_block(out: &mut Encoder, block: Block) {
+    out.put_u32(block.x);
+    out.put_u32(block.y);
+}
+
+pub(super) fn get_block(input: &mut Decoder) -> Result<Block, SaveError> {
+    Ok(Block {
+        x: input.get_u32()?,
+        y: input.get_u32()?,
+    })
+}
+
+fn put_direction(out: &mut Encoder, direction: Direction) {
+    out.put_u8(match direction {
+        Direction::North => 0,
+        Direction::East => 1,
+        Direction::South => 2,
+        Direction::West => 3,
+    });
+}
+
+fn get_direction(input: &mut Decoder) -> Result<Direction, SaveError> {
+    match input.get_u8()? {
+        0 => Ok(Direction::North),
+        1 => Ok(Direction::East),
+        2 => Ok(Direction::South),
+        3 => Ok(Direction::West),
+        other => Err(SaveError::Corrupt(format!("unknown direction {}", other))),
+    }
+}
+
+fn put_player_mode(out: &mut Encoder, mode: PlayerMode) {
+    match mode {
+        PlayerMode::Focus => out.put_u8(0),
+        PlayerMode::Demolish => out.put_u8(1),
+        PlayerMode::RaiseLower { brush } => {
+            out.put_u8(2);
+            out.put_u8(brush.radius);
+            out.put_u8(match brush.shape {
+                BrushShape::Square => 0,
+                BrushShape::Circle => 1,
+            });
+            out.put_u8(match brush.falloff {
+                BrushFalloff::None => 0,
+                BrushFalloff::Smooth => 1,
+            });
+        }
+        PlayerMode::PlaceStructure {
+            structure,
+            orientation,
+        } => {
+            out.put_u8(3);
+            put_structure(out, structure);
+            put_direction(out, orientation);
+        }
+        PlayerMode::Flatten { anchor, level } => {
+            out.put_u8(4);
+            out.put_bool(anchor.is_some());
+            if let Some(anchor) = anchor {
+                put_block(out, anchor);
+            }
+            match level {
+                FlattenLevel::Average => out.put_u8(0),
+                FlattenLevel::Height(h) => {
+                    out.put_u8(1);
+                    out.put_u8(h);
+                }
+            }
+        }
+    }
+}
+
+/// Reads the player's mode, refusing brushes, anchors and levels the tools could never produce on
+/// this board.
+fn get_player_mode(input: &mut Decoder, board: &Board) -> Result<PlayerMode, SaveError> {
+    match input.get_u8()? {
+        0 => Ok(PlayerMode::Focus),
+        1 => Ok(PlayerMode::Demolish),
+        2 => {
+            let radius = input.get_u8()?;
+            if radius > MAX_BRUSH_RADIUS {
+                return Err(SaveError::Corrupt(format!(
+                    "brush radius {} is too large",
+                    radius
+                )));
+            }
+            let shape = match input.get_u8()? {
+                0 => BrushShape::Square,
+                1 => BrushShape::Circle,
+                other => return Err(SaveError::Corrupt(format!("unknown brush shape {}", other))),
+            };
+            let falloff = match input.get_u8()? {
+                0 => BrushFalloff::None,
+                1 => BrushFalloff::Smooth,
+                other => {
+                    return Err(SaveError::Corrupt(format!(
+                        "unknown brush falloff {}",
+                        other
+                    )))
+                }
+            };
+            Ok(PlayerMode::RaiseLower {
+                brush: Brush {
+                    radius,
+                    shape,
+                    falloff,
+                },
+            })
+        }
+        3 => Ok(PlayerMode::PlaceStructure {
+            structure: get_structure(input)?,
+            orientation: get_direction(input)?,
+        }),
+        4 => {
+            let anchor = if input.get_bool()? {
+                let block = get_block(input)?;
+                if block.x >= board.width() || block.y >= board.height() {
+                    return Err(SaveError::Corrupt(format!(
+                        "flatten anchor ({}, {}) is off the board",
+                        block.x, block.y
+                    )));
+                }
+                Some(block)
+            } else {
+                None
+            };
+            let level = match input.get_u8()? {
+                0 => FlattenLevel::Average,
+                1 => {
+                    let height = input.get_u8()?;
+                    if height > MAX_HEIGHT {
+                        return Err(SaveError::Corrupt(format!(
+                            "flatten level {} is too high",
+                            height
+                        )));
+                    }
+                    FlattenLevel::Height(height)
+                }
+                other => {
+                    return Err(SaveError::Corrupt(format!(
+                        "unknown flatten level {}",
+                        other
+                    )))
+                }
+            };
+            Ok(PlayerMode::Flatten { anchor, level })
+        }
+        other => Err(SaveError::Corrupt(format!("unknown player mode {}", other))),
+    }
+}
+
+fn put_structure(out: &mut Encoder, structure: Structure) {
+    let tag = match structure {
+        Structure::Forest => 0,
+        Structure::CityRoad => 1,
+        Structure::ApartmentBuilding => 2,
+        Structure::CulturalCenter => 3,
+        Structure::TennisCourt => 4,
+        Structure::SwimmingPool => 5,
+        Structure::SportsStadium => 6,
+        Structure::RaceTrack => 7,
+        Structure::University => 8,
+        Structure::AmusementPark => 9,
+        Structure::LumberMill => 10,
+        Structure::ChemicalPlant => 11,
+        Structure::SteelMill => 12,
+        Structure::Mine { .. } => 13,
+        Structure::Street => 14,
+        Structure::Rails => 15,
+        Structure::Bridge => 16,
+        Structure::Tunnel => 17,
+        Structure::TruckDepot => 18,
+        Structure::TrainStation => 19,
+        Structure::TrainPlatform => 20,
+        Structure::Harbor => 21,
+        Structure::Airport => 22,
+        Structure::AutomobileFactory => 23,
+        Structure::Woodshop => 24,
+        Structure::ElectronicsFactory => 25,
+        Structure::SportsEquipmentFactory => 26,
+        Structure::ToyFactory => 27,
+        Structure::JewelryFactory => 28,
+        Structure::Warehouse => 29,
+        Structure::BuildingEquipmentFactory => 30,
+        Structure::PaperFactory => 31,
+        Structure::PrintingPress => 32,
+        Structure::ToyStore => 33,
+        Structure::SportingGoodsStore => 34,
+        Structure::FurnitureStore => 35,
+        Structure::Jeweler => 36,
+        Structure::ElectronicsStore => 37,
+        Structure::CarDealership => 38,
+        Structure::BuildingEquipmentStore => 39,
+        Structure::StationaryStore => 40,
+    };
+    out.put_u8(tag);
+    if let Structure::Mine { mineral } = structure {
+        out.put_u8(match mineral {
+            Mineral::Gold => 0,
+            Mineral::Silver => 1,
+            Mineral::Diamonds => 2,
+        });
+    }
+}
+
+fn get_structure(input: &mut Decoder) -> Result<Structure, SaveError> {
+    let structure = match input.get_u8()? {
+        0 => Structure::Forest,
+        1 => Structure::CityRoad,
+        2 => Structure::ApartmentBuilding,
+        3 => Structure::CulturalCenter,
+        4 => Structure::TennisCourt,
+        5 => Structure::SwimmingPool,
+        6 => Structure::SportsStadium,
+        7 => Structure::RaceTrack,
+        8 => Structure::University,
+        9 => Structure::AmusementPark,
+        10 => Structure::LumberMill,
+        11 => Structure::ChemicalPlant,
+        12 => Structure::SteelMill,
+        13 => {
+            let mineral = match input.get_u8()? {
+                0 => Mineral::Gold,
+                1 => Mineral::Silver,
+                2 => Mineral::Diamonds,
+                other => return Err(SaveError::Corrupt(format!("unknown mineral {}", other))),
+            };
+            Structure::Mine { mineral }
+        }
+        14 => Structure::Street,
+        15 => Structure::Rails,
+        16 => Structure::Bridge,
+        17 => Structure::Tunnel,
+        18 => Structure::TruckDepot,
+        19 => Structure::TrainStation,
+        20 => Structure::TrainPlatform,
+        21 => Structure::Harbor,
+        22 => Structure::Airport,
+        23 => Structure::AutomobileFactory,
+        24 => Structure::Woodshop,
+        25 => Structure::ElectronicsFactory,
+        26 => Structure::SportsEquipmentFactory,
+        27 => Structure::ToyFactory,
+        28 => Structure::JewelryFactory,
+        29 => Structure::Warehouse,
+        30 => Structure::BuildingEquipmentFactory,
+        31 => Structure::PaperFactory,
+        32 => Structure::PrintingPress,
+        33 => Structure::ToyStore,
+        34 => Structure::SportingGoodsStore,
+        35 => Structure::FurnitureStore,
+        36 => Structure::Jeweler,
+        37 => Structure::ElectronicsStore,
+        38 => Structure::CarDealership,
+        39 => Structure::BuildingEquipmentStore,
+        40 => Structure::StationaryStore,
+        other => return Err(SaveError::Corrupt(format!("unknown structure {}", other))),
+    };
+    Ok(structure)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::StructureId;
+    use crate::state::company::LedgerCategory;
+    use crate::storage::text_map;
+
+    /// The same game saved by every version of the format, oldest first. It's a 64x64 board with
+    /// two forests and a diamond mine that reuses the slot of a demolished forest, while the
+    /// player lines up a train station facing west.
+    const FIXTURES: [&[u8]; SAVE_VERSION as usize] = [
+        include_bytes!("fixtures/v1.sav"),
+        include_bytes!("fixtures/v2.sav"),
+        include_bytes!("fixtures/v3.sav"),
+        include_bytes!("fixtures/v4.sav"),
+        include_bytes!("fixtures/v5.sav"),
+    ];
+
+    fn structure_ids(game: &GameState) -> Vec<StructureId> {
+        game.board.structures().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn loads_fixtures_from_every_version() {
+        for (idx, fixture) in FIXTURES.iter().enumerate() {
+            let game = decode_game(fixture)
+                .unwrap_or_else(|e| panic!("version {} failed to load: {}", idx + 1, e));
+
+            assert_eq!(game.board.width(), 64);
+            assert_eq!(game.board.height(), 64);
+            assert_eq!(game.focal_point, Vertex { x: 30, y: 25 });
+            assert_eq!(game.date, START_DATE);
+            assert_eq!(game.company.cash, STARTING_CASH);
+            assert!(
+                game.player_mode
+                    == PlayerMode::PlaceStructure {
+                        structure: Structure::TrainStation,
+                        orientation: Direction::West,
+                    }
+            );
+
+            assert_eq!(game.board.structures().count(), 3);
+            assert!(game.board.structure_at(Block { x: 0, y: 0 }).is_some());
+            assert!(game.board.structure_at(Block { x: 2, y: 0 }).is_some());
+            let mine = game.board.structure_at(Block { x: 3, y: 0 }).unwrap();
+            let placement = game.board.structure(mine).unwrap();
+            assert!(
+                placement.structure()
+                    == Structure::Mine {
+                        mineral: Mineral::Diamonds
+                    }
+            );
+            assert!(placement.orientation() == Direction::East);
+        }
+    }
+
+    #[test]
+    fn migrated_saves_match_current_saves() {
+        let current = decode_game(FIXTURES[FIXTURES.len() - 1]).unwrap();
+        for fixture in FIXTURES.iter() {
+            let game = decode_game(fixture).unwrap();
+            assert_eq!(structure_ids(&game), structure_ids(&current));
+            assert_eq!(encode_game(&game), encode_game(&current));
+        }
+    }
+
+    #[test]
+    fn reads_metadata_without_the_board() {
+        for fixture in FIXTURES.iter() {
+            let header = &fixture[..MAX_METADATA_LEN as usize];
+            let metadata = decode_metadata(header).unwrap();
+            assert_eq!(metadata, SaveMetadata::of(&decode_game(fixture).unwrap()));
+            assert_eq!((metadata.width, metadata.height), (64, 64));
+        }
+    }
+
+    #[test]
+    fn current_saves_round_trip() {
+        let bytes = FIXTURES[FIXTURES.len() - 1];
+        assert_eq!(encode_game(&decode_game(bytes).unwrap()), bytes);
+    }
+
+    #[test]
+    fn ledgers_round_trip() {
+        let mut game = decode_game(FIXTURES[FIXTURES.len() - 1]).unwrap();
+        game.company.record(LedgerCategory::Construction, -25_000);
+        game.company.ledger.close_month(1950, 1);
+        game.company.record(LedgerCategory::Sales, 4_000);
+        game.company.ledger.close_year(1950);
+
+        let loaded = decode_game(&encode_game(&game)).unwrap();
+        assert_eq!(loaded.company.cash, STARTING_CASH - 21_000);
+        let ledger = &loaded.company.ledger;
+        assert_eq!(ledger.this_month.income(LedgerCategory::Sales), 4_000);
+        assert_eq!(ledger.past_months.len(), 1);
+        let (year, month, january) = ledger.past_months[0];
+        assert_eq!((year, month), (1950, 1));
+        assert_eq!(january.expenses(LedgerCategory::Construction), 25_000);
+        assert_eq!(ledger.past_years[0].1.profit(), -21_000);
+        assert_eq!(ledger.this_year.profit(), 0);
+    }
+
+    #[test]
+    fn games_on_small_text_maps_save_and_load() {
+        let map =
+            "size 4 4\nheights\n00000\n01110\n01110\n01110\n00000\nstructures\nForest North 1 1\n";
+        let board =
+            text_map::parse_map(&text_map::write_map(&text_map::parse_map(map).unwrap())).unwrap();
+        let game = GameState::new(board);
+
+        let loaded = decode_game(&encode_game(&game)).unwrap();
+        assert_eq!((loaded.board.width(), loaded.board.height()), (4, 4));
+        assert_eq!(
+            text_map::write_map(&loaded.board),
+            text_map::write_map(&game.board)
+        );
+    }
+
+    #[test]
+    fn impossible_player_modes_are_corrupt() {
+        let mut game = decode_game(FIXTURES[FIXTURES.len() - 1]).unwrap();
+        let modes = [
+            PlayerMode::RaiseLower {
+                brush: Brush {
+                    radius: u8::MAX,
+                    shape: BrushShape::Circle,
+                    falloff: BrushFalloff::Smooth,
+                },
+            },
+            PlayerMode::Flatten {
+                anchor: Some(Block { x: 64, y: 0 }),
+                level: FlattenLevel::Average,
+            },
+            PlayerMode::Flatten {
+                anchor: None,
+                level: FlattenLevel::Height(MAX_HEIGHT + 1),
+            },
+        ];
+        for &mode in modes.iter() {
+            game.player_mode = mode;
+            match decode_game(&encode_game(&game)) {
+                Err(SaveError::Corrupt(_)) => {}
+                _ => panic!("expected the player mode to be refused"),
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_saves_are_errors() {
+        for fixture in FIXTURES.iter() {
+            for len in 0..fixture.len() {
+                assert!(decode_game(&fixture[..len]).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut bytes = FIXTURES[FIXTURES.len() - 1].to_vec();
+        let newer = SAVE_VERSION + 1;
+        bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&newer.to_le_bytes());
+        match decode_game(&bytes) {
+            Err(SaveError::UnsupportedVersion(version)) => assert_eq!(version, newer),
+            _ => panic!("expected version {} to be refused", newer),
+        }
+    }
+}
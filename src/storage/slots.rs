@@ -0,0 +1,251 @@
+//! Named save slots and the rotating set of autosaves, all kept as files in one directory.
+
+use super::save::{self, SaveError, SaveMetadata};
+use crate::state::game::{GameState, DEFAULT_SAVE_NAME};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const SAVE_DIR: &str = "saves";
+const SAVE_EXTENSION: &str = "sav";
+const AUTOSAVE_PREFIX: &str = "autosave-";
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct AutosaveSettings {
+    /// Time between autosaves, or None to never autosave.
+    pub interval: Option<Duration>,
+    /// How many autosaves are kept before the oldest is replaced. At least one is always kept.
+    pub count: usize,
+}
+
+impl AutosaveSettings {
+    pub fn new() -> Self {
+        // Five minutes apart, keeping the last quarter of an hour.
+        Self {
+            interval: Some(Duration::from_secs(5 * 60)),
+            count: 3,
+        }
+    }
+}
+
+impl Default for AutosaveSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SlotKind {
+    Manual,
+    /// Numbered from 1, the most recent.
+    Autosave(usize),
+}
+
+/// A save slot as shown on the load screen.
+#[derive(Clone, Debug)]
+pub struct SlotSummary {
+    pub kind: SlotKind,
+    pub label: String,
+    pub path: PathBuf,
+    /// The reason the save can't be read if it is damaged, so the slot can still be listed.
+    pub metadata: Result<SaveMetadata, String>,
+}
+
+/// Saves the game into the manual slot in `dir` named after it.
+pub fn save_to_slot(game: &GameState, dir: &Path) -> Result<PathBuf, SaveError> {
+    let path = slot_path(dir, &slot_file_name(&game.save_name));
+    save::save_game(game, &path)?;
+    Ok(path)
+}
+
+/// Saves the game as the most recent autosave in `dir`, moving each older autosave down a place and
+/// dropping the oldest once there are `count` of them. Autosaves kept while the count was higher
+/// are dropped too.
+pub fn autosave(game: &GameState, dir: &Path, count: usize) -> Result<PathBuf, SaveError> {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if autosave_number(&path).is_some_and(|n| n > count) {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    for n in (1..count).rev() {
+        let from = autosave_path(dir, n);
+        if from.exists() {
+            fs::rename(&from, autosave_path(dir, n + 1))?;
+        }
+    }
+    let path = autosave_path(dir, 1);
+    save::save_game(game, &path)?;
+    Ok(path)
+}
+
+/// Loads the game in a slot. Games saved before they had a name take the name of their slot.
+pub fn load_slot(slot: &SlotSummary) -> Result<GameState, SaveError> {
+    let mut game = save::load_game(&slot.path)?;
+    if game.save_name.is_empty() {
+        game.save_name = match slot.kind {
+            SlotKind::Manual => slot.label.clone(),
+            SlotKind::Autosave(_) => String::from(DEFAULT_SAVE_NAME),
+        };
+    }
+    Ok(game)
+}
+
+/// Every save in `dir`: the autosaves from newest to oldest, then the manual slots
+/// in alphabetical order.
+pub fn list_slots(dir: &Path) -> Vec<SlotSummary> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut slots: Vec<SlotSummary> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == SAVE_EXTENSION))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_string();
+            let kind = match stem.strip_prefix(AUTOSAVE_PREFIX) {
+                Some(_) => SlotKind::Autosave(autosave_number(&path)?),
+                None => SlotKind::Manual,
+            };
+            let metadata = save::load_metadata(&path).map_err(|e| e.to_string());
+            // Prefer the name the player gave, since the file name may have been cleaned up.
+            let label = match (kind, &metadata) {
+                (SlotKind::Autosave(n), _) => format!("Autosave {}", n),
+                (SlotKind::Manual, Ok(metadata)) if !metadata.name.is_empty() => {
+                    metadata.name.clone()
+                }
+                (SlotKind::Manual, _) => stem,
+            };
+            Some(SlotSummary {
+                kind,
+                label,
+                path,
+                metadata,
+            })
+        })
+        .collect();
+
+    slots.sort_by(|a, b| match (a.kind, b.kind) {
+        (SlotKind::Autosave(a), SlotKind::Autosave(b)) => a.cmp(&b),
+        (SlotKind::Autosave(_), SlotKind::Manual) => std::cmp::Ordering::Less,
+        (SlotKind::Manual, SlotKind::Autosave(_)) => std::cmp::Ordering::Greater,
+        (SlotKind::Manual, SlotKind::Manual) => a.label.cmp(&b.label),
+    });
+    slots
+}
+
+fn slot_path(dir: &Path, file_name: &str) -> PathBuf {
+    dir.join(file_name).with_extension(SAVE_EXTENSION)
+}
+
+fn autosave_path(dir: &Path, n: usize) -> PathBuf {
+    slot_path(dir, &format!("{}{}", AUTOSAVE_PREFIX, n))
+}
+
+/// The number of the autosave at `path`, or None if it isn't an autosave.
+fn autosave_number(path: &Path) -> Option<usize> {
+    if path.extension()? != SAVE_EXTENSION {
+        return None;
+    }
+    path.file_stem()?
+        .to_str()?
+        .strip_prefix(AUTOSAVE_PREFIX)?
+        .parse()
+        .ok()
+}
+
+/// Keeps letters, digits, spaces, dashes and underscores so any name makes a safe file name that
+/// can't be mistaken for an autosave.
+fn slot_file_name(name: &str) -> String {
+    let file_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let file_name = file_name.trim();
+    if file_name.is_empty() || file_name.starts_with(AUTOSAVE_PREFIX) {
+        format!("_{}", file_name)
+    } else {
+        file_name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::Board;
+    use std::env;
+
+    /// An empty directory of its own for each test, which the test removes once it is done.
+    fn save_dir(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("titan-slots-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn game_named(name: &str) -> GameState {
+        let mut game = GameState::new(Board::new(64, 64));
+        game.save_name = name.to_string();
+        game
+    }
+
+    /// The names of the games in the autosaves, newest first.
+    fn autosave_names(dir: &Path) -> Vec<String> {
+        list_slots(dir)
+            .into_iter()
+            .map(|slot| {
+                assert!(matches!(slot.kind, SlotKind::Autosave(_)));
+                slot.metadata.unwrap().name
+            })
+            .collect()
+    }
+
+    #[test]
+    fn autosaves_rotate_and_drop_the_oldest() {
+        let dir = save_dir("rotate");
+        for name in ["first", "second", "third", "fourth"].iter() {
+            autosave(&game_named(name), &dir, 3).unwrap();
+        }
+        assert_eq!(autosave_names(&dir), vec!["fourth", "third", "second"]);
+        assert!(!autosave_path(&dir, 4).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn autosaves_beyond_a_lowered_count_are_deleted() {
+        let dir = save_dir("surplus");
+        for name in ["first", "second", "third", "fourth", "fifth"].iter() {
+            autosave(&game_named(name), &dir, 5).unwrap();
+        }
+        save_to_slot(&game_named("kept"), &dir).unwrap();
+
+        autosave(&game_named("sixth"), &dir, 2).unwrap();
+        let slots = list_slots(&dir);
+        let labels: Vec<&str> = slots.iter().map(|slot| slot.label.as_str()).collect();
+        assert_eq!(labels, vec!["Autosave 1", "Autosave 2", "kept"]);
+        assert_eq!(slots[1].metadata.as_ref().unwrap().name, "fifth");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn slot_names_are_cleaned_into_safe_file_names() {
+        assert_eq!(slot_file_name("My Town"), "My Town");
+        assert_eq!(slot_file_name("Rail & Sons!"), "Rail _ Sons_");
+        assert_eq!(slot_file_name("../../etc/passwd"), "______etc_passwd");
+        assert_eq!(slot_file_name("  padded  "), "padded");
+        assert_eq!(slot_file_name("autosave-1"), "_autosave-1");
+        assert_eq!(slot_file_name("   "), "_");
+        assert_eq!(slot_file_name("café"), "caf_");
+    }
+}
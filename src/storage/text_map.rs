@@ -0,0 +1,347 @@
+//! A plain-text description of a board that is easy to read, edit by hand and diff.
+//!
+//! ```text
+//! # Lines starting with '#' and blank lines are ignored.
+//! size 4 3
+//! heights
+//! 00000
+//! 01110
+//! 01210
+//! 00000
+//! structures
+//! Forest North 1 1
+//! Mine(Gold) East 2 1
+//! ```
+//!
+//! `size` gives the board in blocks. `heights` is followed by one row of digits per row of
+//! vertices, so a board of width x height blocks has height + 1 rows of width + 1 digits.
+//! `structures` lists each placement as its structure, orientation and origin block. Structures
+//! are placed in the order listed and must obey the same rules as placing them in the game.
+
+use crate::state::board::{
+    Block, Board, Direction, Mineral, Structure, Vertex, VertexHeight, MAX_BOARD_SIZE, MAX_HEIGHT,
+};
+use std::fs;
+use std::path::Path;
+
+const STRUCTURE_NAMES: [(&str, Structure); 43] = [
+    ("Forest", Structure::Forest),
+    ("CityRoad", Structure::CityRoad),
+    ("ApartmentBuilding", Structure::ApartmentBuilding),
+    ("CulturalCenter", Structure::CulturalCenter),
+    ("TennisCourt", Structure::TennisCourt),
+    ("SwimmingPool", Structure::SwimmingPool),
+    ("SportsStadium", Structure::SportsStadium),
+    ("RaceTrack", Structure::RaceTrack),
+    ("University", Structure::University),
+    ("AmusementPark", Structure::AmusementPark),
+    ("LumberMill", Structure::LumberMill),
+    ("ChemicalPlant", Structure::ChemicalPlant),
+    ("SteelMill", Structure::SteelMill),
+    (
+        "Mine(Gold)",
+        Structure::Mine {
+            mineral: Mineral::Gold,
+        },
+    ),
+    (
+        "Mine(Silver)",
+        Structure::Mine {
+            mineral: Mineral::Silver,
+        },
+    ),
+    (
+        "Mine(Diamonds)",
+        Structure::Mine {
+            mineral: Mineral::Diamonds,
+        },
+    ),
+    ("Street", Structure::Street),
+    ("Rails", Structure::Rails),
+    ("Bridge", Structure::Bridge),
+    ("Tunnel", Structure::Tunnel),
+    ("TruckDepot", Structure::TruckDepot),
+    ("TrainStation", Structure::TrainStation),
+    ("TrainPlatform", Structure::TrainPlatform),
+    ("Harbor", Structure::Harbor),
+    ("Airport", Structure::Airport),
+    ("AutomobileFactory", Structure::AutomobileFactory),
+    ("Woodshop", Structure::Woodshop),
+    ("ElectronicsFactory", Structure::ElectronicsFactory),
+    ("SportsEquipmentFactory", Structure::SportsEquipmentFactory),
+    ("ToyFactory", Structure::ToyFactory),
+    ("JewelryFactory", Structure::JewelryFactory),
+    ("Warehouse", Structure::Warehouse),
+    (
+        "BuildingEquipmentFactory",
+        Structure::BuildingEquipmentFactory,
+    ),
+    ("PaperFactory", Structure::PaperFactory),
+    ("PrintingPress", Structure::PrintingPress),
+    ("ToyStore", Structure::ToyStore),
+    ("SportingGoodsStore", Structure::SportingGoodsStore),
+    ("FurnitureStore", Structure::FurnitureStore),
+    ("Jeweler", Structure::Jeweler),
+    ("ElectronicsStore", Structure::ElectronicsStore),
+    ("CarDealership", Structure::CarDealership),
+    ("BuildingEquipmentStore", Structure::BuildingEquipmentStore),
+    ("StationaryStore", Structure::StationaryStore),
+];
+
+const DIRECTION_NAMES: [(&str, Direction); 4] = [
+    ("North", Direction::North),
+    ("East", Direction::East),
+    ("South", Direction::South),
+    ("West", Direction::West),
+];
+
+pub fn load_map<P: AsRef<Path>>(path: P) -> Result<Board, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_map(&text)
+}
+
+pub fn save_map<P: AsRef<Path>>(board: &Board, path: P) -> Result<(), String> {
+    fs::write(path, write_map(board)).map_err(|e| e.to_string())
+}
+
+pub fn write_map(board: &Board) -> String {
+    let mut text = String::new();
+    text.push_str(&format!("size {} {}\n", board.width(), board.height()));
+
+    text.push_str("heights\n");
+    for y in 0..(board.height() + 1) {
+        for x in 0..(board.width() + 1) {
+            let h = board.vertex_height(Vertex { x, y });
+            text.push(std::char::from_digit(h as u32, 10).unwrap_or('?'));
+        }
+        text.push('\n');
+    }
+
+    text.push_str("structures\n");
+    for (_, placement) in board.structures() {
+        let origin = placement.origin();
+        text.push_str(&format!(
+            "{} {} {} {}\n",
+            structure_name(placement.structure()),
+            direction_name(placement.orientation()),
+            origin.x,
+            origin.y
+        ));
+    }
+
+    text
+}
+
+/// Builds a board from its text description. Errors give the line number they were found on.
+pub fn parse_map(text: &str) -> Result<Board, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let mut next_line = |expected: &str| {
+        lines
+            .next()
+            .ok_or_else(|| format!("map ends before {}", expected))
+    };
+
+    let (line_num, line) = next_line("its size")?;
+    let at = |reason: String| format!("line {}: {}", line_num, reason);
+    let (width, height) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["size", width, height] => (
+            parse_dimension(width).map_err(at)?,
+            parse_dimension(height).map_err(at)?,
+        ),
+        _ => return Err(at(String::from("expected `size <width> <height>`"))),
+    };
+
+    let (line_num, line) = next_line("its heights")?;
+    if line != "heights" {
+        return Err(format!("line {}: expected `heights`", line_num));
+    }
+    let mut board = Board::new(width, height);
+    for y in 0..(height + 1) {
+        let (line_num, line) = next_line("the last row of heights")?;
+        let row: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        if row.len() != (width + 1) as usize {
+            return Err(format!(
+                "line {}: expected {} heights but found {}",
+                line_num,
+                width + 1,
+                row.len()
+            ));
+        }
+        for (x, &c) in row.iter().enumerate() {
+            let h = match c.to_digit(10) {
+                Some(h) if h <= MAX_HEIGHT as u32 => h as VertexHeight,
+                _ => {
+                    return Err(format!(
+                        "line {}: `{}` is not a height from 0 to {}",
+                        line_num, c, MAX_HEIGHT
+                    ))
+                }
+            };
+            let v = Vertex { x: x as u32, y };
+            board.set_vertex_height(v, h);
+            if let Some(n) = steep_neighbour(&board, v) {
+                return Err(format!(
+                    "line {}: vertex ({}, {}) is more than one height from vertex ({}, {})",
+                    line_num, v.x, v.y, n.x, n.y
+                ));
+            }
+        }
+    }
+
+    match next_line("its structures") {
+        Ok((_, "structures")) => {}
+        Ok((line_num, _)) => return Err(format!("line {}: expected `structures`", line_num)),
+        Err(_) => return Ok(board),
+    }
+    for (line_num, line) in lines {
+        let at = |reason: String| format!("line {}: {}", line_num, reason);
+        let (structure, orientation, x, y) = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [structure, orientation, x, y] => (structure, orientation, x, y),
+            _ => {
+                return Err(at(String::from(
+                    "expected `<structure> <direction> <x> <y>`",
+                )))
+            }
+        };
+        let structure = parse_structure(structure).map_err(at)?;
+        let orientation = parse_direction(orientation).map_err(at)?;
+        let origin = Block {
+            x: x.parse()
+                .map_err(|_| at(format!("`{}` is not a block position", x)))?,
+            y: y.parse()
+                .map_err(|_| at(format!("`{}` is not a block position", y)))?,
+        };
+        board
+            .place_structure(structure, orientation, origin)
+            .map_err(|e| at(e.to_string()))?;
+    }
+
+    Ok(board)
+}
+
+pub fn structure_name(structure: Structure) -> &'static str {
+    STRUCTURE_NAMES
+        .iter()
+        .find(|(_, s)| *s == structure)
+        .map(|(name, _)| *name)
+        .unwrap_or("?")
+}
+
+pub fn parse_structure(name: &str) -> Result<Structure, String> {
+    STRUCTURE_NAMES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, s)| *s)
+        .ok_or_else(|| format!("`{}` is not a structure", name))
+}
+
+pub fn direction_name(direction: Direction) -> &'static str {
+    DIRECTION_NAMES
+        .iter()
+        .find(|(_, d)| *d == direction)
+        .map(|(name, _)| *name)
+        .unwrap_or("?")
+}
+
+pub fn parse_direction(name: &str) -> Result<Direction, String> {
+    DIRECTION_NAMES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, d)| *d)
+        .ok_or_else(|| format!("`{}` is not a direction", name))
+}
+
+fn parse_dimension(value: &str) -> Result<u32, String> {
+    match value.parse() {
+        Ok(size) if (1..=MAX_BOARD_SIZE).contains(&size) => Ok(size),
+        _ => Err(format!(
+            "`{}` is not a board size from 1 to {}",
+            value, MAX_BOARD_SIZE
+        )),
+    }
+}
+
+/// A neighbour already read, above or to the left, that the vertex is too steep a climb from.
+fn steep_neighbour(board: &Board, v: Vertex) -> Option<Vertex> {
+    let h = board.vertex_height(v);
+    [(-1, 0), (-1, -1), (0, -1), (1, -1)]
+        .iter()
+        .map(|&(dx, dy)| (v.x as i64 + dx, v.y as i64 + dy))
+        .filter(|&(x, y)| x >= 0 && y >= 0 && x <= board.width() as i64)
+        .map(|(x, y)| Vertex {
+            x: x as u32,
+            y: y as u32,
+        })
+        .find(|&n| (board.vertex_height(n) as i32 - h as i32).abs() > 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HILL: &str = "
+        # A small hill with a forest on top and a mine on its side.
+        size 4 3
+        heights
+        00000
+        01110
+        01210
+        01110
+        structures
+        Forest North 1 1
+        Mine(Gold) East 2 1
+    ";
+
+    #[test]
+    fn parses_heights_and_structures() {
+        let board = parse_map(HILL).unwrap();
+        assert_eq!((board.width(), board.height()), (4, 3));
+        assert_eq!(board.vertex_height(Vertex { x: 2, y: 2 }), 2);
+        assert_eq!(board.vertex_height(Vertex { x: 4, y: 3 }), 0);
+        assert!(board.block_structure_type(Block { x: 1, y: 1 }) == Some(Structure::Forest));
+        let mine = board.structure_at(Block { x: 3, y: 2 }).unwrap();
+        assert!(board.structure(mine).unwrap().orientation() == Direction::East);
+    }
+
+    #[test]
+    fn writes_what_it_parses() {
+        let board = parse_map(HILL).unwrap();
+        let text = write_map(&board);
+        assert_eq!(write_map(&parse_map(&text).unwrap()), text);
+        assert!(text.contains("01210\n"));
+        assert!(text.contains("Mine(Gold) East 2 1\n"));
+    }
+
+    #[test]
+    fn every_structure_has_a_name() {
+        for &(name, structure) in STRUCTURE_NAMES.iter() {
+            assert!(parse_structure(name).unwrap() == structure);
+            assert_eq!(structure_name(structure), name);
+        }
+    }
+
+    fn parse_error(text: &str) -> String {
+        match parse_map(text) {
+            Ok(_) => panic!("expected the map to be refused"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn errors_give_the_line() {
+        let cliff = "size 2 1\nheights\n000\n030\n";
+        assert_eq!(
+            parse_error(cliff),
+            "line 4: vertex (1, 1) is more than one height from vertex (0, 1)"
+        );
+        let overlap =
+            "size 2 2\nheights\n000\n010\n000\nstructures\nForest North 0 0\nForest North 0 0\n";
+        assert!(parse_error(overlap).starts_with("line 8: "));
+        let unknown = "size 1 1\nheights\n00\n00\nstructures\nCastle North 0 0\n";
+        assert_eq!(parse_error(unknown), "line 6: `Castle` is not a structure");
+    }
+}
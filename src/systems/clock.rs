@@ -0,0 +1,137 @@
+use crate::state::calendar::{GameSpeed, TICKS_PER_DAY};
+use crate::state::game::GameState;
+use crate::systems::finance;
+
+/// Systems which run every simulation tick, in order.
+const TICK_SYSTEMS: &[fn(&mut GameState)] = &[];
+
+/// Systems which run at the start of every new day, in order, after the date has moved on.
+/// Interest can leave the company short of cash, so the cost preview is checked again last.
+const DAY_SYSTEMS: &[fn(&mut GameState)] = &[
+    finance::apply_new_day,
+    finance::apply_check_solvency,
+    finance::refresh_affordability,
+];
+
+pub fn apply_toggle_pause(game: &mut GameState) {
+    game.paused = !game.paused;
+}
+
+pub fn apply_set_speed(game: &mut GameState, speed: GameSpeed) {
+    game.speed = speed;
+    game.paused = false;
+}
+
+/// Runs the simulation for one update of the game loop, taking as many steps as the game speed
+/// calls for, or none while the game is paused.
+pub fn apply_update(game: &mut GameState) {
+    if game.is_paused() {
+        return;
+    }
+    for _ in 0..game.speed.steps() {
+        apply_tick(game);
+    }
+}
+
+/// Advances the simulation by one fixed step, turning the calendar over once a day's worth of
+/// ticks have passed.
+pub fn apply_tick(game: &mut GameState) {
+    for system in TICK_SYSTEMS {
+        system(game);
+    }
+
+    game.day_ticks += 1;
+    if game.day_ticks >= TICKS_PER_DAY {
+        game.day_ticks = 0;
+        game.date = game.date.next_day();
+        for system in DAY_SYSTEMS {
+            system(game);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::GameAction;
+    use crate::state::board::Board;
+    use crate::state::calendar::GameDate;
+    use crate::state::menu::building::BuyBuildingScreenState;
+    use crate::state::menu::sidebar::SidebarMenu;
+    use crate::systems;
+
+    fn run_days(game: &mut GameState, days: u32) {
+        for _ in 0..days * TICKS_PER_DAY {
+            apply_tick(game);
+        }
+    }
+
+    #[test]
+    fn days_turn_over_months_and_years() {
+        let mut game = GameState::new(Board::new(16, 16));
+        run_days(&mut game, 31);
+        assert_eq!(
+            game.date,
+            GameDate {
+                year: 1950,
+                month: 2,
+                day: 1
+            }
+        );
+        assert_eq!(game.day_ticks, 0);
+
+        run_days(&mut game, 334);
+        assert_eq!(
+            game.date,
+            GameDate {
+                year: 1951,
+                month: 1,
+                day: 1
+            }
+        );
+
+        // 1952 is a leap year.
+        run_days(&mut game, 365 + 31 + 28);
+        assert_eq!(
+            game.date,
+            GameDate {
+                year: 1952,
+                month: 2,
+                day: 29
+            }
+        );
+        apply_tick(&mut game);
+        assert_eq!(
+            game.date,
+            GameDate {
+                year: 1952,
+                month: 2,
+                day: 29
+            }
+        );
+        assert_eq!(game.day_ticks, 1);
+    }
+
+    #[test]
+    fn updates_follow_the_speed_and_pauses() {
+        let mut game = GameState::new(Board::new(16, 16));
+        apply_set_speed(&mut game, GameSpeed::X8);
+        apply_update(&mut game);
+        assert_eq!(game.day_ticks, 8);
+
+        apply_toggle_pause(&mut game);
+        apply_update(&mut game);
+        assert_eq!(game.day_ticks, 8);
+
+        apply_toggle_pause(&mut game);
+        let menu = SidebarMenu::Building;
+        systems::apply_action(&mut game, GameAction::OpenMenu { menu });
+        apply_update(&mut game);
+        assert_eq!(game.day_ticks, 8);
+
+        systems::apply_action(&mut game, GameAction::CloseBuyBuildingScreen);
+        assert!(game.buy_building_screen == BuyBuildingScreenState::Hidden);
+        apply_update(&mut game);
+        assert_eq!(game.day_ticks, 16);
+    }
+}
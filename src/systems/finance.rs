@@ -0,0 +1,405 @@
+use crate::state::board::{MAX_HEIGHT, MIN_HEIGHT};
+use crate::state::company::{
+    format_money, LedgerCategory, Money, ANNUAL_INTEREST_PERCENT, BANKRUPTCY_DAYS,
+    LOAN_LIMIT_PERCENT, LOAN_STEP,
+};
+use crate::state::game::{CostPreview, GameState, PlayerMode};
+use crate::state::history::Edit;
+use crate::state::menu::finances::FinancesScreenState;
+use crate::systems::terrain;
+
+pub fn apply_display_finances_screen(game: &mut GameState) {
+    game.finances_screen = FinancesScreenState::Visible;
+}
+
+pub fn apply_close_finances_screen(game: &mut GameState) {
+    game.finances_screen = FinancesScreenState::Hidden;
+}
+
+/// What the company is worth: its cash and what it has built, less what it owes.
+pub fn company_value(game: &GameState) -> Money {
+    let structures: Money = game
+        .board
+        .structures()
+        .map(|(_, placement)| placement.structure().cost())
+        .sum();
+    game.company.cash + structures - game.company.loan
+}
+
+/// The most the bank will lend, a share of the company's value in whole loan steps.
+pub fn loan_limit(game: &GameState) -> Money {
+    let limit = company_value(game) * LOAN_LIMIT_PERCENT / 100;
+    limit.max(0) / LOAN_STEP * LOAN_STEP
+}
+
+/// What the loan costs in interest each month.
+pub fn monthly_interest(loan: Money) -> Money {
+    loan * ANNUAL_INTEREST_PERCENT / 100 / 12
+}
+
+/// Borrows another loan step, or as much of one as the bank will still lend.
+pub fn apply_borrow(game: &mut GameState) {
+    let limit = loan_limit(game);
+    let amount = LOAN_STEP.min(limit - game.company.loan);
+    if amount <= 0 {
+        println!(
+            "Borrowing failed: the bank won't lend more than {}.",
+            format_money(limit)
+        );
+        return;
+    }
+
+    let company = &mut game.company;
+    company.loan += amount;
+    company.cash += amount;
+    println!(
+        "Borrowed {}. The company owes {}.",
+        format_money(amount),
+        format_money(company.loan)
+    );
+}
+
+/// Repays a loan step, or whatever is left of the loan if that's less.
+pub fn apply_repay_loan(game: &mut GameState) {
+    let company = &mut game.company;
+    let amount = LOAN_STEP.min(company.loan);
+    if amount == 0 {
+        println!("Nothing to repay.");
+        return;
+    }
+    if let Err(e) = company.check_funds(amount) {
+        println!("Repayment failed: {}", e);
+        return;
+    }
+
+    company.loan -= amount;
+    company.cash -= amount;
+    println!(
+        "Repaid {}. The company owes {}.",
+        format_money(amount),
+        format_money(company.loan)
+    );
+}
+
+/// Pays or receives money for something the player did. Returns the payment as an edit, so undoing
+/// what was paid for settles up again.
+pub fn pay(game: &mut GameState, category: LedgerCategory, amount: Money) -> Edit {
+    game.company.record(category, amount);
+    Edit::Payment { category, amount }
+}
+
+/// Works out what the action the player is lining up would cost, and whether the company can pay
+/// each charge it could lead to. Raising and lowering are charged separately, while building on
+/// uneven ground pays for the levelling and the structure together.
+pub fn refresh_cost_preview(game: &mut GameState) {
+    let mut preview = Vec::new();
+    let mut charges = Vec::new();
+    match (game.player_mode, game.highlighted_block) {
+        (PlayerMode::RaiseLower { brush }, Some(block)) => {
+            // The brush always moves the vertex under the cursor, so there is no point planning
+            // a raise or lower which that vertex has no room for.
+            let center = block.into();
+            let height = game.board.vertex_height(center);
+            let policy = game.structure_policy();
+            if height < MAX_HEIGHT {
+                if let Ok(plan) = terrain::plan_raise(&game.board, center, &brush, policy) {
+                    preview.push(("Raise", plan.cost()));
+                    charges.push(plan.cost());
+                }
+            }
+            if height > MIN_HEIGHT {
+                if let Ok(plan) = terrain::plan_lower(&game.board, center, &brush, policy) {
+                    preview.push(("Lower", plan.cost()));
+                    charges.push(plan.cost());
+                }
+            }
+        }
+        (PlayerMode::Flatten { .. }, _) => {
+            if let Some(Ok(plan)) = &game.terrain_preview {
+                preview.push(("Level", plan.cost()));
+                charges.push(plan.cost());
+            }
+        }
+        (PlayerMode::PlaceStructure { structure, .. }, _) => {
+            preview.push(("Build", structure.cost()));
+            let mut charge = structure.cost();
+            if let Some(pending) = game.pending_flatten {
+                preview.push(("Level", pending.level_cost));
+                charge += pending.level_cost;
+            }
+            charges.push(charge);
+        }
+        (PlayerMode::Demolish, Some(block)) => {
+            if let Some(placement) = game
+                .board
+                .structure_at(block)
+                .and_then(|id| game.board.structure(id))
+            {
+                preview.push(("Refund", -game.refund(placement.structure())));
+            }
+        }
+        _ => {}
+    }
+    game.cost_preview = CostPreview {
+        lines: preview,
+        charges,
+        unaffordable: false,
+    };
+    refresh_affordability(game);
+}
+
+/// Checks the previewed charges against the company's cash again, for when the cash has changed
+/// but what the player is lining up has not.
+pub fn refresh_affordability(game: &mut GameState) {
+    let company = &game.company;
+    let preview = &mut game.cost_preview;
+    preview.unaffordable = preview
+        .charges
+        .iter()
+        .any(|&charge| company.check_funds(charge).is_err());
+}
+
+/// Charges a month's interest on the loan and closes the ledger's month when a new one begins, and
+/// closes its year too when that month is January.
+pub fn apply_new_day(game: &mut GameState) {
+    let date = game.date;
+    if date.day != 1 {
+        return;
+    }
+
+    let interest = monthly_interest(game.company.loan);
+    if interest > 0 {
+        game.company.record(LedgerCategory::Operations, -interest);
+    }
+
+    let ledger = &mut game.company.ledger;
+    if date.month == 1 {
+        ledger.close_month(date.year - 1, 12);
+        ledger.close_year(date.year - 1);
+    } else {
+        ledger.close_month(date.year, date.month - 1);
+    }
+}
+
+/// Counts the days the company's cash stays below zero, and declares it bankrupt once there have
+/// been too many in a row.
+pub fn apply_check_solvency(game: &mut GameState) {
+    let company = &mut game.company;
+    if company.cash >= 0 {
+        company.days_in_debt = 0;
+        return;
+    }
+
+    company.days_in_debt = company.days_in_debt.saturating_add(1);
+    if company.days_in_debt == 1 {
+        println!(
+            "The company is out of cash and goes bankrupt unless it's back in the black within {} days.",
+            BANKRUPTCY_DAYS
+        );
+    }
+    if company.days_in_debt >= BANKRUPTCY_DAYS && !company.bankrupt {
+        company.bankrupt = true;
+        println!("The company has gone bankrupt. Game over.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Block, Board, Direction, Structure, Vertex};
+    use crate::state::calendar::TICKS_PER_DAY;
+    use crate::state::company::STARTING_CASH;
+    use crate::state::game::Brush;
+    use crate::state::terrain::COST_PER_HEIGHT_CHANGE;
+    use crate::storage::text_map::parse_map;
+    use crate::systems::{clock, structure};
+
+    fn run_days(game: &mut GameState, days: u32) {
+        for _ in 0..days * TICKS_PER_DAY {
+            clock::apply_tick(game);
+        }
+    }
+
+    #[test]
+    fn work_is_paid_for_and_demolition_refunded() {
+        let plateau = "
+            size 4 4
+            heights
+            00000
+            01110
+            01110
+            01110
+            00000
+        ";
+        let mut game = GameState::new(parse_map(plateau).unwrap());
+        let block = Block { x: 2, y: 2 };
+        game.highlighted_block = Some(block);
+        game.player_mode = PlayerMode::PlaceStructure {
+            structure: Structure::TruckDepot,
+            orientation: Direction::North,
+        };
+        game.company.cash = Structure::TruckDepot.cost() - 1;
+        structure::apply_place_structure(&mut game);
+        assert_eq!(game.board.structures().count(), 0);
+
+        game.company.cash = STARTING_CASH;
+        structure::apply_place_structure(&mut game);
+        assert_eq!(game.board.structures().count(), 1);
+        assert_eq!(
+            game.company.cash,
+            STARTING_CASH - Structure::TruckDepot.cost()
+        );
+
+        game.demolition_refund = 20;
+        game.player_mode = PlayerMode::Demolish;
+        structure::apply_demolish(&mut game);
+        let ledger = &game.company.ledger;
+        assert_eq!(
+            ledger.this_month.income(LedgerCategory::Construction),
+            Structure::TruckDepot.cost() / 5
+        );
+
+        // Raising a single vertex in the middle of the plateau moves it and nothing else.
+        game.player_mode = PlayerMode::RaiseLower {
+            brush: Brush::new(),
+        };
+        refresh_cost_preview(&mut game);
+        assert_eq!(
+            game.cost_preview.lines[0],
+            ("Raise", COST_PER_HEIGHT_CHANGE)
+        );
+        game.company.cash = COST_PER_HEIGHT_CHANGE - 1;
+        terrain::apply_raise_terrain(&mut game);
+        assert_eq!(game.board.vertex_height(block.into()), 1);
+        game.company.cash = COST_PER_HEIGHT_CHANGE;
+        terrain::apply_raise_terrain(&mut game);
+        assert_eq!(game.board.vertex_height(block.into()), 2);
+        assert_eq!(game.company.cash, 0);
+    }
+
+    #[test]
+    fn costs_charged_together_are_checked_together() {
+        let hill = "
+            size 4 4
+            heights
+            00000
+            01110
+            01210
+            01110
+            00000
+        ";
+        let mut game = GameState::new(parse_map(hill).unwrap());
+        game.highlighted_block = Some(Block { x: 1, y: 1 });
+        game.player_mode = PlayerMode::PlaceStructure {
+            structure: Structure::TruckDepot,
+            orientation: Direction::North,
+        };
+        structure::apply_place_structure(&mut game);
+        assert!(game.pending_flatten.is_some());
+
+        // Enough for the depot or for levelling, but not for both.
+        let levelling = COST_PER_HEIGHT_CHANGE;
+        game.company.cash = Structure::TruckDepot.cost();
+        refresh_cost_preview(&mut game);
+        assert_eq!(game.cost_preview.lines[1], ("Level", levelling));
+        assert!(game.cost_preview.unaffordable);
+
+        game.company.cash = Structure::TruckDepot.cost() + levelling;
+        refresh_cost_preview(&mut game);
+        assert!(!game.cost_preview.unaffordable);
+    }
+
+    #[test]
+    fn previews_plan_what_the_brush_can_do_and_follow_the_cash() {
+        // A pyramid stepping up from the shore to a peak at the maximum height.
+        let mut board = Board::new(12, 12);
+        for y in 0..=12 {
+            for x in 0..=12 {
+                let distance = (x as i32 - 6).abs().max((y as i32 - 6).abs());
+                board.set_vertex_height(Vertex { x, y }, (MAX_HEIGHT as i32 - distance) as u8);
+            }
+        }
+        let mut game = GameState::new(board);
+        game.highlighted_block = Some(Block { x: 6, y: 6 });
+        game.player_mode = PlayerMode::RaiseLower {
+            brush: Brush::new(),
+        };
+        // The peak is already as high as it goes, so only lowering it is priced.
+        refresh_cost_preview(&mut game);
+        let lowering = game.cost_preview.lines[0].1;
+        assert_eq!(game.cost_preview.lines, vec![("Lower", lowering)]);
+        assert!(!game.cost_preview.unaffordable);
+
+        // A month's interest leaves the company short without the player doing anything.
+        game.company.cash = lowering;
+        game.company.loan = LOAN_STEP;
+        run_days(&mut game, 31);
+        assert!(game.company.cash < lowering);
+        assert!(game.cost_preview.unaffordable);
+    }
+
+    #[test]
+    fn months_and_years_close_as_the_calendar_turns() {
+        let mut game = GameState::new(Board::new(16, 16));
+        game.company.record(LedgerCategory::Terraforming, -500);
+        run_days(&mut game, 30);
+        assert!(game.company.ledger.past_months.is_empty());
+
+        run_days(&mut game, 1);
+        let ledger = &game.company.ledger;
+        assert_eq!(ledger.past_months.len(), 1);
+        let (year, month, january) = ledger.past_months[0];
+        assert_eq!((year, month), (1950, 1));
+        assert_eq!(january.expenses(LedgerCategory::Terraforming), 500);
+        assert_eq!(ledger.this_month.total_expenses(), 0);
+        assert_eq!(ledger.this_year.total_expenses(), 500);
+
+        run_days(&mut game, 334);
+        let ledger = &game.company.ledger;
+        assert_eq!(ledger.past_months.len(), 12);
+        assert_eq!(ledger.past_months[0].1, 12);
+        assert_eq!(ledger.past_years.len(), 1);
+        assert_eq!(ledger.past_years[0].0, 1950);
+        assert_eq!(ledger.past_years[0].1.total_expenses(), 500);
+        assert_eq!(ledger.this_year.total_expenses(), 0);
+    }
+
+    #[test]
+    fn loans_charge_interest_and_lasting_debt_bankrupts() {
+        let mut game = GameState::new(Board::new(16, 16));
+        let limit = loan_limit(&game);
+        assert_eq!(limit, STARTING_CASH * LOAN_LIMIT_PERCENT / 100);
+        for _ in 0..limit / LOAN_STEP + 1 {
+            apply_borrow(&mut game);
+        }
+        assert_eq!(game.company.loan, limit);
+        assert_eq!(game.company.cash, STARTING_CASH + limit);
+        assert_eq!(loan_limit(&game), limit);
+
+        apply_repay_loan(&mut game);
+        let loan = limit - LOAN_STEP;
+        assert_eq!(game.company.loan, loan);
+
+        run_days(&mut game, 31);
+        let (_, _, january) = game.company.ledger.past_months[0];
+        assert_eq!(
+            january.expenses(LedgerCategory::Operations),
+            monthly_interest(loan)
+        );
+        assert_eq!(
+            game.company.cash,
+            STARTING_CASH + loan - monthly_interest(loan)
+        );
+
+        // Repaying needs the cash to do it.
+        game.company.cash = -1;
+        apply_repay_loan(&mut game);
+        assert_eq!(game.company.loan, loan);
+
+        run_days(&mut game, BANKRUPTCY_DAYS as u32 - 1);
+        assert!(!game.company.bankrupt);
+        run_days(&mut game, 1);
+        assert!(game.company.bankrupt);
+        assert!(game.is_paused());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::state::board::{Board, Vertex, VertexHeight, MAX_HEIGHT, WATER_LEVEL};
+use crate::systems::terrain::limit_slopes;
+
+/// How much taller a mountain is than the tallest rolling hill, relative to the noise range.
+const MOUNTAIN_PEAK: f32 = 0.8;
+const MIN_MOUNTAIN_RADIUS: u32 = 4;
+const MAX_MOUNTAIN_RADIUS: u32 = 12;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct TerrainParams {
+    pub seed: u64,
+    /// Fraction of the board, from 0.0 to 1.0, which should end up under water.
+    pub water_ratio: f32,
+    /// How much fine detail survives, from 0.0 (gentle hills) to 1.0 (jagged terrain).
+    pub roughness: f32,
+    pub mountains: u32,
+}
+
+impl TerrainParams {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            water_ratio: 0.3,
+            roughness: 0.5,
+            mountains: 3,
+        }
+    }
+}
+
+/// Replaces every vertex height on the board with generated terrain.
+/// The same parameters always produce the same terrain on the same size of board.
+pub fn generate_terrain(board: &mut Board, params: &TerrainParams) {
+    let width = board.width();
+    let height = board.height();
+    let mut rng = Rng::new(params.seed);
+
+    let mut field = fractal_noise(&mut rng, width, height, params.roughness);
+    for _ in 0..params.mountains {
+        add_mountain(&mut rng, &mut field, width, height);
+    }
+
+    // Only interior vertices count towards the water ratio since edge vertices are always water.
+    let mut interior: Vec<f32> = Vec::new();
+    for y in 1..height {
+        for x in 1..width {
+            interior.push(field[field_index(width, x, y)]);
+        }
+    }
+    interior.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if interior.is_empty() {
+        return;
+    }
+
+    let water_ratio = params.water_ratio.clamp(0.0, 1.0);
+    let shoreline = interior[(water_ratio * (interior.len() - 1) as f32).round() as usize];
+    let peak = interior[interior.len() - 1];
+
+    for y in 0..(height + 1) {
+        for x in 0..(width + 1) {
+            let v = Vertex { x, y };
+            let f = field[field_index(width, x, y)];
+            let h = if v.is_edge_vertex(board) || (water_ratio > 0.0 && f <= shoreline) {
+                WATER_LEVEL
+            } else if peak <= shoreline {
+                WATER_LEVEL + 1
+            } else {
+                // Spread the land above the shoreline evenly across the available heights.
+                let normalised = (f - shoreline) / (peak - shoreline);
+                let levels = (MAX_HEIGHT - WATER_LEVEL) as f32;
+                let h = WATER_LEVEL + 1 + (normalised * levels) as VertexHeight;
+                h.min(MAX_HEIGHT)
+            };
+            board.set_vertex_height(v, h);
+        }
+    }
+
+    limit_slopes(board);
+}
+
+fn field_index(width: u32, x: u32, y: u32) -> usize {
+    (y * (width + 1) + x) as usize
+}
+
+/// Value noise summed over several octaves, normalised to the range 0.0 to 1.0.
+/// Each octave halves the lattice spacing and scales the amplitude by `roughness`.
+fn fractal_noise(rng: &mut Rng, width: u32, height: u32, roughness: f32) -> Vec<f32> {
+    let roughness = roughness.clamp(0.0, 1.0);
+    let mut field = vec![0.0; ((width + 1) * (height + 1)) as usize];
+
+    let mut spacing = (width.max(height) / 4).max(2);
+    let mut amplitude = 1.0;
+    let mut total_amplitude = 0.0;
+    while spacing >= 2 {
+        let lattice_width = width / spacing + 2;
+        let lattice_height = height / spacing + 2;
+        let lattice: Vec<f32> = (0..(lattice_width * lattice_height))
+            .map(|_| rng.next_f32())
+            .collect();
+        let at = |lx: u32, ly: u32| lattice[(ly * lattice_width + lx) as usize];
+
+        for y in 0..(height + 1) {
+            for x in 0..(width + 1) {
+                let (lx, ly) = (x / spacing, y / spacing);
+                let fx = smoothstep((x % spacing) as f32 / spacing as f32);
+                let fy = smoothstep((y % spacing) as f32 / spacing as f32);
+                let top = lerp(at(lx, ly), at(lx + 1, ly), fx);
+                let bottom = lerp(at(lx, ly + 1), at(lx + 1, ly + 1), fx);
+                field[field_index(width, x, y)] += lerp(top, bottom, fy) * amplitude;
+            }
+        }
+
+        total_amplitude += amplitude;
+        amplitude *= roughness;
+        spacing /= 2;
+    }
+
+    for f in field.iter_mut() {
+        *f /= total_amplitude;
+    }
+
+    field
+}
+
+fn add_mountain(rng: &mut Rng, field: &mut [f32], width: u32, height: u32) {
+    let cx = rng.range(1, width.max(2)) as i64;
+    let cy = rng.range(1, height.max(2)) as i64;
+    let radius = rng.range(MIN_MOUNTAIN_RADIUS, MAX_MOUNTAIN_RADIUS + 1) as i64;
+
+    for y in (cy - radius).max(0)..(cy + radius + 1).min(height as i64 + 1) {
+        for x in (cx - radius).max(0)..(cx + radius + 1).min(width as i64 + 1) {
+            let d2 = ((x - cx) * (x - cx) + (y - cy) * (y - cy)) as f32;
+            let r2 = (radius * radius) as f32;
+            if d2 < r2 {
+                let falloff = 1.0 - d2 / r2;
+                field[field_index(width, x as u32, y as u32)] += MOUNTAIN_PEAK * falloff * falloff;
+            }
+        }
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// SplitMix64. Kept in-tree rather than taken from a crate so a seed keeps producing the same map
+/// no matter which versions of our dependencies are in use.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in [0.0, 1.0).
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniformly distributed in [low, high).
+    fn range(&mut self, low: u32, high: u32) -> u32 {
+        low + (self.next_u64() % (high - low) as u64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(seed: u64) -> Board {
+        let mut board = Board::new(64, 48);
+        generate_terrain(&mut board, &TerrainParams::new(seed));
+        board
+    }
+
+    /// The biggest height difference between any vertex and one of its eight neighbours.
+    fn steepest_slope(board: &Board) -> u8 {
+        let mut steepest = 0;
+        for y in 0..=board.height() {
+            for x in 0..=board.width() {
+                let h = board.vertex_height(Vertex { x, y }) as i32;
+                for &(dx, dy) in [(1, 0), (1, 1), (0, 1), (-1, 1)].iter() {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || nx > board.width() as i32 || ny > board.height() as i32 {
+                        continue;
+                    }
+                    let n = board.vertex_height(Vertex {
+                        x: nx as u32,
+                        y: ny as u32,
+                    }) as i32;
+                    steepest = steepest.max((h - n).unsigned_abs() as u8);
+                }
+            }
+        }
+        steepest
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_map() {
+        assert_eq!(generate(42).vertex_heights(), generate(42).vertex_heights());
+        assert_ne!(generate(42).vertex_heights(), generate(43).vertex_heights());
+    }
+
+    #[test]
+    fn generated_maps_have_no_cliffs() {
+        for seed in 0..4 {
+            let board = generate(seed);
+            assert!(board.vertex_heights().iter().any(|&h| h > WATER_LEVEL));
+            assert!(steepest_slope(&board) <= 1);
+        }
+    }
+
+    #[test]
+    fn slopes_are_limited_by_lowering() {
+        let mut board = Board::new(8, 8);
+        board.set_vertex_height(Vertex { x: 4, y: 4 }, MAX_HEIGHT);
+        board.set_vertex_height(Vertex { x: 1, y: 6 }, 3);
+        limit_slopes(&mut board);
+        assert_eq!(steepest_slope(&board), 1);
+        assert_eq!(board.vertex_height(Vertex { x: 4, y: 4 }), WATER_LEVEL + 1);
+        assert_eq!(board.vertex_height(Vertex { x: 1, y: 6 }), WATER_LEVEL + 1);
+
+        let mut board = Board::new(8, 8);
+        for x in 2..=6 {
+            for y in 2..=6 {
+                board.set_vertex_height(Vertex { x, y }, MAX_HEIGHT);
+            }
+        }
+        limit_slopes(&mut board);
+        assert_eq!(steepest_slope(&board), 1);
+        assert_eq!(board.vertex_height(Vertex { x: 4, y: 4 }), 3);
+    }
+}
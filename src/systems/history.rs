@@ -0,0 +1,211 @@
+use crate::state::board::{Board, StructurePlacement};
+use crate::state::company::{LedgerCategory, Money};
+use crate::state::game::GameState;
+use crate::state::history::Edit;
+use crate::state::terrain::TerrainPlan;
+use crate::systems::terrain;
+
+pub fn apply_undo(game: &mut GameState) {
+    match game.history.pop_undo() {
+        Some(edit) => match apply_to_copy(game, &edit.inverse()) {
+            Ok(()) => {
+                println!("Undone.");
+                game.history.push_redo(edit);
+            }
+            Err(e) => println!("Undo failed: {}", e),
+        },
+        None => println!("Nothing to undo."),
+    }
+}
+
+pub fn apply_redo(game: &mut GameState) {
+    match game.history.pop_redo() {
+        Some(edit) => match apply_to_copy(game, &edit) {
+            Ok(()) => {
+                println!("Redone.");
+                game.history.push_undo(edit);
+            }
+            Err(e) => println!("Redo failed: {}", e),
+        },
+        None => println!("Nothing to redo."),
+    }
+}
+
+/// Takes back an edit that has just been made, without touching the history.
+pub fn revert(game: &mut GameState, edit: &Edit) -> Result<(), String> {
+    apply_to_copy(game, &edit.inverse())
+}
+
+/// The edit made by carrying out a terrain plan, given the structures it bulldozed.
+pub fn terrain_edit(plan: &TerrainPlan, demolished: Vec<StructurePlacement>) -> Edit {
+    Edit::Terrain {
+        changes: plan.changes().to_vec(),
+        demolished,
+        restored: Vec::new(),
+    }
+}
+
+/// Makes the edit on a copy of the board, so a failure part way through leaves the game as it was.
+/// The edit's payments are only made once the board has been changed.
+fn apply_to_copy(game: &mut GameState, edit: &Edit) -> Result<(), String> {
+    let mut payments = Vec::new();
+    collect_payments(edit, &mut payments);
+    let total: Money = payments.iter().map(|&(_, amount)| amount).sum();
+    game.company.check_funds(-total)?;
+
+    let mut board = game.board.clone();
+    apply_edit(&mut board, edit)?;
+    game.board = board;
+    for (category, amount) in payments {
+        game.company.record(category, amount);
+    }
+    game.pending_flatten = None;
+    terrain::refresh_flatten_preview(game);
+    Ok(())
+}
+
+fn apply_edit(board: &mut Board, edit: &Edit) -> Result<(), String> {
+    match edit {
+        Edit::Terrain {
+            changes,
+            demolished,
+            restored,
+        } => {
+            for placement in demolished.iter() {
+                demolish(board, placement)?;
+            }
+            for change in changes.iter() {
+                if board.vertex_height(change.vertex) != change.from {
+                    return Err(format!("the ground at {:?} has changed", change.vertex));
+                }
+                board.set_vertex_height(change.vertex, change.to);
+            }
+            for placement in restored.iter() {
+                place(board, placement)?;
+            }
+        }
+        Edit::Place { placement } => place(board, placement)?,
+        Edit::Demolish { placement } => demolish(board, placement)?,
+        Edit::Payment { .. } => {}
+        Edit::Group(edits) => {
+            for edit in edits.iter() {
+                apply_edit(board, edit)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn collect_payments(edit: &Edit, payments: &mut Vec<(LedgerCategory, Money)>) {
+    match edit {
+        Edit::Payment { category, amount } => payments.push((*category, *amount)),
+        Edit::Group(edits) => {
+            for edit in edits.iter() {
+                collect_payments(edit, payments);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn place(board: &mut Board, placement: &StructurePlacement) -> Result<(), String> {
+    board
+        .place_structure(
+            placement.structure(),
+            placement.orientation(),
+            placement.origin(),
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn demolish(board: &mut Board, placement: &StructurePlacement) -> Result<(), String> {
+    let id = board
+        .structure_at(placement.origin())
+        .filter(|&id| board.structure(id) == Some(placement))
+        .ok_or_else(|| format!("the structure at {:?} has gone", placement.origin()))?;
+    board.demolish(id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Block, Direction, Structure};
+    use crate::state::company::STARTING_CASH;
+    use crate::state::game::{Brush, PlayerMode};
+    use crate::storage::text_map::{parse_map, write_map};
+    use crate::systems::structure;
+
+    const PLATEAU: &str = "
+        size 6 6
+        heights
+        0000000
+        0111110
+        0111110
+        0111110
+        0111110
+        0111110
+        0000000
+    ";
+
+    fn place_forest(game: &mut GameState, block: Block) {
+        game.player_mode = PlayerMode::PlaceStructure {
+            structure: Structure::Forest,
+            orientation: Direction::North,
+        };
+        game.highlighted_block = Some(block);
+        structure::apply_place_structure(game);
+    }
+
+    #[test]
+    fn undoes_and_redoes_in_order() {
+        let mut game = GameState::new(parse_map(PLATEAU).unwrap());
+        let start = write_map(&game.board);
+
+        place_forest(&mut game, Block { x: 2, y: 2 });
+        let placed = write_map(&game.board);
+
+        // Raising a corner of the forest's block bulldozes it.
+        game.bulldoze = true;
+        game.player_mode = PlayerMode::RaiseLower {
+            brush: Brush::new(),
+        };
+        terrain::apply_raise_terrain(&mut game);
+        let raised = write_map(&game.board);
+
+        place_forest(&mut game, Block { x: 4, y: 4 });
+        game.player_mode = PlayerMode::Demolish;
+        structure::apply_demolish(&mut game);
+        let demolished = write_map(&game.board);
+        let cash = game.company.cash;
+        assert!(cash < STARTING_CASH);
+
+        apply_undo(&mut game);
+        apply_undo(&mut game);
+        assert_eq!(write_map(&game.board), raised);
+        apply_undo(&mut game);
+        assert_eq!(write_map(&game.board), placed);
+        apply_undo(&mut game);
+        assert_eq!(write_map(&game.board), start);
+        assert_eq!(game.company.cash, STARTING_CASH);
+
+        for _ in 0..4 {
+            apply_redo(&mut game);
+        }
+        assert_eq!(write_map(&game.board), demolished);
+        assert_eq!(game.company.cash, cash);
+    }
+
+    #[test]
+    fn new_edits_clear_redo() {
+        let mut game = GameState::new(parse_map(PLATEAU).unwrap());
+        place_forest(&mut game, Block { x: 1, y: 1 });
+        apply_undo(&mut game);
+        place_forest(&mut game, Block { x: 3, y: 3 });
+        let placed = write_map(&game.board);
+
+        apply_redo(&mut game);
+        assert_eq!(write_map(&game.board), placed);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::state::board::{Direction, Structure};
+use crate::state::board::Direction;
 use crate::state::game::{GameState, PlayerMode};
-use crate::state::menu::building::{Building, BuyBuildingScreenState, Category};
+use crate::state::menu::building::{Building, BuyBuildingButton, BuyBuildingScreenState, Category};
 use crate::state::menu::sidebar::SidebarMenu;
+use crate::systems::{finance, terrain};
 
 pub fn apply_open_menu(game: &mut GameState, menu: SidebarMenu) {
     game.open_menu = Some(menu);
@@ -11,13 +12,17 @@ pub fn apply_open_menu(game: &mut GameState, menu: SidebarMenu) {
         }
         SidebarMenu::Building => {
             apply_display_building_screen(game);
-            // game.player_mode = PlayerMode::PlaceStructure {
-            //     structure: Structure::TrainStation,
-            //     orientation: Direction::North,
-            // };
         }
+        // The bulldozer both demolishes and terraforms. Pressing it again switches between them.
         SidebarMenu::Demolish => {
-            game.player_mode = PlayerMode::RaiseLower { radius: 0 };
+            if game.player_mode == PlayerMode::Demolish {
+                terrain::apply_select_terraform(game);
+            } else {
+                game.player_mode = PlayerMode::Demolish;
+            }
+        }
+        SidebarMenu::Finances => {
+            finance::apply_display_finances_screen(game);
         }
         // TODO (toby)
         _ => {}
@@ -28,5 +33,98 @@ pub fn apply_display_building_screen(game: &mut GameState) {
     game.buy_building_screen = BuyBuildingScreenState::Visible {
         selected_building: Building::TruckDepot,
         selected_category: Category::Terminals,
+        highlighted: None,
     };
 }
+
+pub fn apply_close_building_screen(game: &mut GameState) {
+    game.buy_building_screen = BuyBuildingScreenState::Hidden;
+}
+
+pub fn apply_buy_building_hover(game: &mut GameState, button: Option<BuyBuildingButton>) {
+    if let BuyBuildingScreenState::Visible { highlighted, .. } = &mut game.buy_building_screen {
+        *highlighted = button;
+    }
+}
+
+/// Shows the buildings in another category, selecting the first of them.
+pub fn apply_select_building_category(game: &mut GameState, category: Category) {
+    if let BuyBuildingScreenState::Visible {
+        selected_building,
+        selected_category,
+        ..
+    } = &mut game.buy_building_screen
+    {
+        if *selected_category != category {
+            *selected_category = category;
+            *selected_building = category.buildings()[0];
+        }
+    }
+}
+
+pub fn apply_select_building(game: &mut GameState, building: Building) {
+    if let BuyBuildingScreenState::Visible {
+        selected_building, ..
+    } = &mut game.buy_building_screen
+    {
+        *selected_building = building;
+    }
+}
+
+/// Closes the screen and lines up the selected building for the player to place.
+pub fn apply_buy_building(game: &mut GameState) {
+    if let BuyBuildingScreenState::Visible {
+        selected_building, ..
+    } = game.buy_building_screen
+    {
+        game.player_mode = PlayerMode::PlaceStructure {
+            structure: selected_building.structure(),
+            orientation: Direction::North,
+        };
+        game.pending_flatten = None;
+        apply_close_building_screen(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Board, Structure};
+
+    #[test]
+    fn buying_a_building_lines_it_up_for_placement() {
+        let mut game = GameState::new(Board::new(16, 16));
+        apply_open_menu(&mut game, SidebarMenu::Building);
+        apply_select_building_category(&mut game, Category::Retail);
+        assert!(
+            game.buy_building_screen
+                == BuyBuildingScreenState::Visible {
+                    selected_building: Building::ToyStore,
+                    selected_category: Category::Retail,
+                    highlighted: None,
+                }
+        );
+
+        apply_select_building(&mut game, Building::Jeweler);
+        apply_buy_building(&mut game);
+        assert!(game.buy_building_screen == BuyBuildingScreenState::Hidden);
+        assert!(
+            game.player_mode
+                == PlayerMode::PlaceStructure {
+                    structure: Structure::Jeweler,
+                    orientation: Direction::North,
+                }
+        );
+    }
+
+    #[test]
+    fn the_bulldozer_switches_between_demolishing_and_terraforming() {
+        let mut game = GameState::new(Board::new(16, 16));
+        apply_open_menu(&mut game, SidebarMenu::Demolish);
+        assert!(game.player_mode == PlayerMode::Demolish);
+        apply_open_menu(&mut game, SidebarMenu::Demolish);
+        assert!(matches!(game.player_mode, PlayerMode::RaiseLower { .. }));
+        apply_open_menu(&mut game, SidebarMenu::Demolish);
+        assert!(game.player_mode == PlayerMode::Demolish);
+    }
+}
@@ -1,4 +1,147 @@
+pub mod clock;
+pub mod finance;
+pub mod generation;
+pub mod history;
 pub mod menu;
 pub mod navigation;
+pub mod replay;
+pub mod save;
 pub mod structure;
 pub mod terrain;
+
+use crate::action::GameAction;
+use crate::state::game::GameState;
+
+/// Carries out an action against the game. Actions which only reach outside the game, such as
+/// exporting the map or quitting, are left to the front end and do nothing here.
+pub fn apply_action(game: &mut GameState, action: GameAction) {
+    match action {
+        GameAction::Hover { block } => {
+            // The cursor moves many times within a block. Nothing shown depends on where in the
+            // block it is, so skip planning the previews again until it reaches another block.
+            if block.is_some() && block == game.highlighted_block {
+                return;
+            }
+            navigation::apply_hover(game, block);
+        }
+        GameAction::ConfirmFlatten => {
+            structure::apply_confirm_flatten(game);
+        }
+        GameAction::Demolish => {
+            structure::apply_demolish(game);
+        }
+        GameAction::Focus => {
+            navigation::apply_focus(game);
+        }
+        GameAction::LowerTerrain => {
+            terrain::apply_lower_terrain(game);
+        }
+        GameAction::OpenMenu { menu } => {
+            menu::apply_open_menu(game, menu);
+        }
+        GameAction::BuyBuildingHover { button } => {
+            menu::apply_buy_building_hover(game, button);
+        }
+        GameAction::SelectBuildingCategory { category } => {
+            menu::apply_select_building_category(game, category);
+        }
+        GameAction::SelectBuilding { building } => {
+            menu::apply_select_building(game, building);
+        }
+        GameAction::BuyBuilding => {
+            menu::apply_buy_building(game);
+        }
+        GameAction::CloseBuyBuildingScreen => {
+            menu::apply_close_building_screen(game);
+        }
+        GameAction::PlaceStructure => {
+            structure::apply_place_structure(game);
+        }
+        GameAction::RaiseTerrain => {
+            terrain::apply_raise_terrain(game);
+        }
+        GameAction::RotateStructure => {
+            structure::apply_rotate_structure(game);
+        }
+        GameAction::ResizeBrush { delta } => {
+            terrain::apply_resize_brush(game, delta);
+        }
+        GameAction::CycleBrushShape => {
+            terrain::apply_cycle_brush_shape(game);
+        }
+        GameAction::ToggleBrushFalloff => {
+            terrain::apply_toggle_brush_falloff(game);
+        }
+        GameAction::SelectTerraform => {
+            terrain::apply_select_terraform(game);
+        }
+        GameAction::ToggleBulldoze => {
+            terrain::apply_toggle_bulldoze(game);
+        }
+        GameAction::SelectFlatten => {
+            terrain::apply_select_flatten(game);
+        }
+        GameAction::Flatten => {
+            terrain::apply_flatten(game);
+        }
+        GameAction::CancelFlatten => {
+            terrain::apply_cancel_flatten(game);
+        }
+        GameAction::AdjustFlattenLevel { delta } => {
+            terrain::apply_adjust_flatten_level(game, delta);
+        }
+        GameAction::SidebarHover { button } => {
+            navigation::apply_sidebar_hover(game, button);
+        }
+        GameAction::SaveGame => {
+            save::apply_save_game(game);
+        }
+        GameAction::LoadGame => {
+            save::apply_load_game(game);
+        }
+        GameAction::OpenLoadScreen => {
+            save::apply_display_load_screen(game);
+        }
+        GameAction::SelectSlot { delta } => {
+            save::apply_select_slot(game, delta);
+        }
+        GameAction::NameSlot => {
+            save::apply_name_slot(game);
+        }
+        GameAction::TypeSlotName { character } => {
+            save::apply_type_slot_name(game, character);
+        }
+        GameAction::EraseSlotName => {
+            save::apply_erase_slot_name(game);
+        }
+        GameAction::CancelSlotName => {
+            save::apply_cancel_slot_name(game);
+        }
+        GameAction::CloseLoadScreen => {
+            save::apply_close_load_screen(game);
+        }
+        GameAction::CloseFinancesScreen => {
+            finance::apply_close_finances_screen(game);
+        }
+        GameAction::Borrow => {
+            finance::apply_borrow(game);
+        }
+        GameAction::RepayLoan => {
+            finance::apply_repay_loan(game);
+        }
+        GameAction::Undo => {
+            history::apply_undo(game);
+        }
+        GameAction::Redo => {
+            history::apply_redo(game);
+        }
+        GameAction::TogglePause => {
+            clock::apply_toggle_pause(game);
+        }
+        GameAction::SetSpeed { speed } => {
+            clock::apply_set_speed(game, speed);
+        }
+        GameAction::ExportHeightmap | GameAction::ExportMap | GameAction::Quit => {}
+    }
+    finance::refresh_cost_preview(game);
+}
@@ -1,6 +1,7 @@
 use crate::state::board::Block;
 use crate::state::game::GameState;
 use crate::state::menu::sidebar::SidebarMenu;
+use crate::systems::terrain;
 
 pub fn apply_focus(game: &mut GameState) {
     if let Some(block) = game.highlighted_block {
@@ -15,6 +16,7 @@ pub fn apply_hover(game: &mut GameState, block: Option<Block>) {
     } else {
         game.highlighted_block = None;
     }
+    terrain::refresh_flatten_preview(game);
 }
 
 pub fn apply_sidebar_hover(game: &mut GameState, button: Option<SidebarMenu>) {
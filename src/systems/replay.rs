@@ -0,0 +1,135 @@
+use crate::action::GameAction;
+use crate::state::game::GameState;
+use crate::storage::recording::{RecordedEntry, RecordedEvent, Recording};
+use crate::storage::save::{self, SaveError};
+use crate::systems;
+use crate::systems::clock;
+
+/// Plays back a recording one tick at a time.
+pub struct Replay {
+    entries: Vec<RecordedEntry>,
+    next: usize,
+}
+
+impl Replay {
+    /// The game the recording started from, and a replay ready to play from tick 0.
+    pub fn start(recording: Recording) -> Result<(GameState, Self), SaveError> {
+        let game = recording.start_game()?;
+        let replay = Self {
+            entries: recording.entries,
+            next: 0,
+        };
+        Ok((game, replay))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.entries.len()
+    }
+
+    /// The tick of the last entry, after which the replay has nothing left to do.
+    pub fn last_tick(&self) -> u64 {
+        self.entries.last().map_or(0, |entry| entry.tick)
+    }
+
+    /// Applies everything recorded up to and including the given tick.
+    pub fn apply_tick(&mut self, game: &mut GameState, tick: u64) {
+        while let Some(entry) = self.entries.get(self.next) {
+            if entry.tick > tick {
+                break;
+            }
+            match &entry.event {
+                // Saving and loading aren't repeated so a replay never touches the save slots.
+                // A game that was loaded follows as a snapshot instead.
+                RecordedEvent::Action(GameAction::SaveGame) => {
+                    let _ = systems::save::apply_take_slot_name(game);
+                }
+                RecordedEvent::Action(GameAction::LoadGame) => {}
+                RecordedEvent::Action(action) => systems::apply_action(game, *action),
+                RecordedEvent::Snapshot(bytes) => match save::decode_game(bytes) {
+                    Ok(snapshot) => *game = snapshot,
+                    Err(e) => println!("Replay snapshot failed to load: {}", e),
+                },
+            }
+            self.next += 1;
+        }
+    }
+}
+
+/// Plays a whole recording back without a window, returning the game as the session left it.
+pub fn replay(recording: Recording) -> Result<GameState, SaveError> {
+    let (mut game, mut replay) = Replay::start(recording)?;
+    for tick in 0..=replay.last_tick() {
+        replay.apply_tick(&mut game, tick);
+        clock::apply_update(&mut game);
+    }
+    Ok(game)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Block, Board};
+    use crate::state::menu::sidebar::SidebarMenu;
+    use crate::storage::recording::{decode_recording, Recorder};
+    use crate::systems::generation::{generate_terrain, TerrainParams};
+
+    fn new_game() -> GameState {
+        let mut board = Board::new(64, 64);
+        generate_terrain(&mut board, &TerrainParams::new(5));
+        GameState::new(board)
+    }
+
+    fn hover(x: u32, y: u32) -> GameAction {
+        GameAction::Hover {
+            block: Some(Block { x, y }),
+        }
+    }
+
+    #[test]
+    fn replays_reproduce_the_session() {
+        let session = [
+            (0, hover(20, 20)),
+            (3, GameAction::PlaceStructure),
+            (3, hover(21, 20)),
+            (10, GameAction::PlaceStructure),
+            (12, GameAction::Undo),
+            (15, GameAction::SelectTerraform),
+            (15, GameAction::ResizeBrush { delta: 2 }),
+            (20, hover(30, 31)),
+            (21, GameAction::RaiseTerrain),
+            (21, GameAction::RaiseTerrain),
+            (
+                40,
+                GameAction::OpenMenu {
+                    menu: SidebarMenu::Demolish,
+                },
+            ),
+            (41, hover(20, 20)),
+            (42, GameAction::Demolish),
+        ];
+
+        let mut game = new_game();
+        let mut recorder = Recorder::new(Vec::new(), &game).unwrap();
+        for &(tick, action) in session.iter() {
+            recorder.record_action(tick, action).unwrap();
+            systems::apply_action(&mut game, action);
+        }
+
+        // Loading a save part way through swaps in a game with no history.
+        let mut loaded = new_game();
+        systems::apply_action(&mut loaded, hover(10, 10));
+        systems::apply_action(&mut loaded, GameAction::PlaceStructure);
+        game = save::decode_game(&save::encode_game(&loaded)).unwrap();
+        recorder.record_snapshot(50, &game).unwrap();
+        for &(tick, action) in [(51, hover(12, 12)), (52, GameAction::PlaceStructure)].iter() {
+            recorder.record_action(tick, action).unwrap();
+            systems::apply_action(&mut game, action);
+        }
+
+        let recording = decode_recording(&recorder.into_inner()).unwrap();
+        let replayed = replay(recording).unwrap();
+        assert!(save::encode_game(&replayed) == save::encode_game(&game));
+        assert_eq!(replayed.board.structures().count(), 2);
+        assert!(replayed.highlighted_block == game.highlighted_block);
+    }
+}
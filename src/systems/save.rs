@@ -0,0 +1,166 @@
+use crate::state::game::{GameState, MAX_SAVE_NAME_LENGTH};
+use crate::state::menu::load::LoadScreenState;
+use crate::storage::slots::{self, AutosaveSettings};
+use std::path::Path;
+
+pub fn apply_display_load_screen(game: &mut GameState) {
+    game.load_screen = LoadScreenState::Visible {
+        slots: slots::list_slots(Path::new(slots::SAVE_DIR)),
+        selected: 0,
+        naming: None,
+    };
+}
+
+pub fn apply_close_load_screen(game: &mut GameState) {
+    game.load_screen = LoadScreenState::Hidden;
+}
+
+pub fn apply_select_slot(game: &mut GameState, delta: i8) {
+    if let LoadScreenState::Visible {
+        slots, selected, ..
+    } = &mut game.load_screen
+    {
+        if !slots.is_empty() {
+            let last = slots.len() as i64 - 1;
+            *selected = (*selected as i64 + delta as i64).clamp(0, last) as usize;
+        }
+    }
+}
+
+/// Starts naming a new slot, beginning with the game's current name.
+pub fn apply_name_slot(game: &mut GameState) {
+    if let LoadScreenState::Visible { naming, .. } = &mut game.load_screen {
+        *naming = Some(game.save_name.clone());
+    }
+}
+
+pub fn apply_type_slot_name(game: &mut GameState, character: char) {
+    if let LoadScreenState::Visible {
+        naming: Some(name), ..
+    } = &mut game.load_screen
+    {
+        if !character.is_control() && name.chars().count() < MAX_SAVE_NAME_LENGTH {
+            name.push(character);
+        }
+    }
+}
+
+pub fn apply_erase_slot_name(game: &mut GameState) {
+    if let LoadScreenState::Visible {
+        naming: Some(name), ..
+    } = &mut game.load_screen
+    {
+        name.pop();
+    }
+}
+
+pub fn apply_cancel_slot_name(game: &mut GameState) {
+    if let LoadScreenState::Visible { naming, .. } = &mut game.load_screen {
+        *naming = None;
+    }
+}
+
+/// Gives the game the name typed for a new slot, if one is being named. A replay does this in
+/// place of saving, so it follows the names the player gave without touching the save slots.
+pub fn apply_take_slot_name(game: &mut GameState) -> Result<(), &'static str> {
+    if let LoadScreenState::Visible { naming, .. } = &mut game.load_screen {
+        if let Some(name) = naming {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err("the slot needs a name");
+            }
+            game.save_name = name.to_string();
+            *naming = None;
+        }
+    }
+    Ok(())
+}
+
+/// Saves into the game's named slot, or the slot being named, then lists the slots again so the
+/// new save shows up.
+pub fn apply_save_game(game: &mut GameState) {
+    if let Err(e) = apply_take_slot_name(game) {
+        println!("Save failed: {}", e);
+        return;
+    }
+
+    match slots::save_to_slot(game, Path::new(slots::SAVE_DIR)) {
+        Ok(path) => {
+            println!("Game saved to {}", path.display());
+            if let LoadScreenState::Visible { .. } = game.load_screen {
+                let slots = slots::list_slots(Path::new(slots::SAVE_DIR));
+                let selected = slots.iter().position(|slot| slot.path == path).unwrap_or(0);
+                game.load_screen = LoadScreenState::Visible {
+                    slots,
+                    selected,
+                    naming: None,
+                };
+            }
+        }
+        Err(e) => println!("Save failed: {}", e),
+    }
+}
+
+/// Replaces the game with the one in the selected slot. The current game is kept if the slot
+/// can't be loaded.
+pub fn apply_load_game(game: &mut GameState) {
+    let slot = match &game.load_screen {
+        LoadScreenState::Visible {
+            slots, selected, ..
+        } => match slots.get(*selected) {
+            Some(slot) => slot.clone(),
+            None => return,
+        },
+        LoadScreenState::Hidden => return,
+    };
+
+    match slots::load_slot(&slot) {
+        Ok(loaded) => {
+            println!("Loaded {}", slot.label);
+            *game = loaded;
+        }
+        Err(e) => println!("Could not load {}: {}", slot.label, e),
+    }
+}
+
+pub fn apply_autosave(game: &GameState, settings: &AutosaveSettings) {
+    match slots::autosave(game, Path::new(slots::SAVE_DIR), settings.count.max(1)) {
+        Ok(path) => println!("Autosaved to {}", path.display()),
+        Err(e) => println!("Autosave failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::Board;
+
+    #[test]
+    fn slots_are_named_by_typing() {
+        let mut game = GameState::new(Board::new(16, 16));
+        game.load_screen = LoadScreenState::Visible {
+            slots: Vec::new(),
+            selected: 0,
+            naming: None,
+        };
+
+        apply_name_slot(&mut game);
+        for _ in 0..game.save_name.len() {
+            apply_erase_slot_name(&mut game);
+        }
+        assert_eq!(
+            apply_take_slot_name(&mut game),
+            Err("the slot needs a name")
+        );
+
+        for character in "Harbor\ttown".chars() {
+            apply_type_slot_name(&mut game, character);
+        }
+        assert_eq!(apply_take_slot_name(&mut game), Ok(()));
+        assert_eq!(game.save_name, "Harbortown");
+        assert!(matches!(
+            game.load_screen,
+            LoadScreenState::Visible { naming: None, .. }
+        ));
+    }
+}
@@ -1,5 +1,14 @@
-use crate::state::board::Direction;
-use crate::state::game::{GameState, PlayerMode};
+use crate::state::board::{
+    Board, Direction, PlacementError, StructurePlacement, Vertex, VertexHeight, WATER_LEVEL,
+};
+use crate::state::company::{format_money, LedgerCategory};
+use crate::state::game::{GameState, PendingPlacement, PlayerMode};
+use crate::state::history::Edit;
+use crate::state::terrain::{StructurePolicy, TerrainError, TerrainPlan};
+use crate::systems::terrain::{self, average_height, plan_flatten};
+use crate::systems::{finance, history};
+use std::collections::HashMap;
+use std::fmt;
 
 pub fn apply_rotate_structure(game: &mut GameState) {
     if let PlayerMode::PlaceStructure {
@@ -18,6 +27,7 @@ pub fn apply_rotate_structure(game: &mut GameState) {
             structure,
             orientation: next_orientation,
         };
+        game.pending_flatten = None;
     }
 }
 
@@ -30,6 +40,250 @@ pub fn apply_place_structure(game: &mut GameState) {
         Some(block),
     ) = (game.player_mode, game.highlighted_block)
     {
-        game.board.place_structure(structure, orientation, block);
+        game.pending_flatten = None;
+        if let Err(e) = game.company.check_funds(structure.cost()) {
+            println!("Placement failed: {}", e);
+            return;
+        }
+        match game.board.place_structure(structure, orientation, block) {
+            Ok(_) => {
+                println!("Structure placed for {}.", format_money(structure.cost()));
+                let edits = vec![
+                    Edit::Place {
+                        placement: StructurePlacement::new(structure, orientation, block),
+                    },
+                    finance::pay(game, LedgerCategory::Construction, -structure.cost()),
+                ];
+                game.history.record(Edit::Group(edits));
+            }
+            Err(PlacementError::UnevenTerrain) => {
+                let mut pending = PendingPlacement {
+                    structure,
+                    orientation,
+                    origin: block,
+                    level_cost: 0,
+                };
+                match plan_auto_flatten(&game.board, &pending) {
+                    Ok(plan) => {
+                        pending.level_cost = plan.cost();
+                        game.pending_flatten = Some(pending);
+                        println!(
+                            "The ground is not level. Press Enter to level it for {} and build.",
+                            format_money(plan.cost())
+                        );
+                    }
+                    Err(e) => println!("Placement failed: the ground is not level ({}).", e),
+                }
+            }
+            Err(e) => println!("Placement failed: {}", e),
+        }
+    }
+}
+
+/// Levels the ground under the pending placement, then places it.
+/// The placement is checked against the levelled heights before anything is committed.
+pub fn apply_confirm_flatten(game: &mut GameState) {
+    if let Some(pending) = game.pending_flatten.take() {
+        let plan = match plan_auto_flatten(&game.board, &pending) {
+            Ok(plan) => plan,
+            Err(e) => {
+                println!("Levelling failed: {}", e);
+                return;
+            }
+        };
+        let cost = plan.cost() + pending.structure.cost();
+        if let Err(e) = game.company.check_funds(cost) {
+            println!("Placement failed: {}", e);
+            return;
+        }
+        let mut edits = match terrain::commit_plan(game, &plan) {
+            Ok((edits, _)) => edits,
+            Err(e) => {
+                println!("Levelling failed: {}", e);
+                return;
+            }
+        };
+        if let Err(e) =
+            game.board
+                .place_structure(pending.structure, pending.orientation, pending.origin)
+        {
+            // Levelling on its own is not what the player asked for, so take it back.
+            println!("Placement failed: {}", e);
+            if let Err(e) = history::revert(game, &Edit::Group(edits)) {
+                println!("Levelling could not be undone: {}", e);
+            }
+            return;
+        }
+
+        println!(
+            "Ground levelled and structure placed for {}.",
+            format_money(cost)
+        );
+        edits.push(Edit::Place {
+            placement: StructurePlacement::new(
+                pending.structure,
+                pending.orientation,
+                pending.origin,
+            ),
+        });
+        let structure_cost = pending.structure.cost();
+        edits.push(finance::pay(
+            game,
+            LedgerCategory::Construction,
+            -structure_cost,
+        ));
+        game.history.record(Edit::Group(edits));
+    }
+}
+
+/// Works out how to level the ground under a placement, making sure the structure will fit once
+/// the ground is level. The footprint is levelled to its average height, but never below land.
+fn plan_auto_flatten(
+    board: &Board,
+    pending: &PendingPlacement,
+) -> Result<TerrainPlan, AutoFlattenError> {
+    let (w, h) = pending.structure.footprint(pending.orientation);
+    let (w, h) = (w as u32, h as u32);
+    let height = average_height(board, pending.origin, w, h).max(WATER_LEVEL + 1);
+    let plan = plan_flatten(
+        board,
+        pending.origin,
+        w,
+        h,
+        height,
+        StructurePolicy::Protect,
+    )
+    .map_err(AutoFlattenError::Terrain)?;
+
+    // Check the placement against the heights the plan leaves behind, without touching the board.
+    let levelled: HashMap<Vertex, VertexHeight> = plan
+        .changes()
+        .iter()
+        .map(|change| (change.vertex, change.to))
+        .collect();
+    board
+        .can_place_on(
+            pending.structure,
+            pending.orientation,
+            pending.origin,
+            |v| {
+                levelled
+                    .get(&v)
+                    .copied()
+                    .unwrap_or_else(|| board.vertex_height(v))
+            },
+        )
+        .map_err(AutoFlattenError::Placement)?;
+
+    Ok(plan)
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum AutoFlattenError {
+    Terrain(TerrainError),
+    Placement(PlacementError),
+}
+
+impl fmt::Display for AutoFlattenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AutoFlattenError::Terrain(e) => e.fmt(f),
+            AutoFlattenError::Placement(e) => write!(f, "still cannot build once levelled: {}", e),
+        }
+    }
+}
+
+pub fn apply_demolish(game: &mut GameState) {
+    if let (PlayerMode::Demolish, Some(block)) = (game.player_mode, game.highlighted_block) {
+        match game.board.demolish_at(block) {
+            Some(placement) => {
+                let refund = game.refund(placement.structure());
+                println!(
+                    "Structure at {:?} demolished, refunding {}.",
+                    placement.origin(),
+                    format_money(refund)
+                );
+                let edits = vec![
+                    Edit::Demolish { placement },
+                    finance::pay(game, LedgerCategory::Construction, refund),
+                ];
+                game.history.record(Edit::Group(edits));
+            }
+            None => println!("Nothing to demolish."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Block, Structure};
+    use crate::storage::text_map::{parse_map, write_map};
+
+    #[test]
+    fn uneven_ground_is_levelled_before_building() {
+        let hill = "
+            size 4 4
+            heights
+            00000
+            01110
+            01210
+            01110
+            00000
+        ";
+        let mut game = GameState::new(parse_map(hill).unwrap());
+        game.highlighted_block = Some(Block { x: 1, y: 1 });
+        game.player_mode = PlayerMode::PlaceStructure {
+            structure: Structure::TruckDepot,
+            orientation: Direction::North,
+        };
+
+        // Offering to level the ground leaves the board as it was.
+        let before = write_map(&game.board);
+        apply_place_structure(&mut game);
+        assert!(game.pending_flatten.is_some());
+        assert_eq!(write_map(&game.board), before);
+
+        apply_confirm_flatten(&mut game);
+        assert!(game.pending_flatten.is_none());
+        assert_eq!(game.board.vertex_height(Vertex { x: 2, y: 2 }), 1);
+        assert!(
+            game.board.block_structure_type(Block { x: 1, y: 1 }) == Some(Structure::TruckDepot)
+        );
+    }
+
+    #[test]
+    fn levelling_is_refused_when_the_structure_still_would_not_fit() {
+        let bump = "
+            size 6 6
+            heights
+            0000000
+            0111110
+            0121110
+            0111110
+            0111110
+            0111110
+            0000000
+        ";
+        let mut game = GameState::new(parse_map(bump).unwrap());
+        game.highlighted_block = Some(Block { x: 1, y: 1 });
+        game.player_mode = PlayerMode::PlaceStructure {
+            structure: Structure::University,
+            orientation: Direction::North,
+        };
+        apply_place_structure(&mut game);
+        assert!(game.pending_flatten.is_some());
+
+        // Levelling only lowers the bump, so it would go ahead, but the forest leaves no room.
+        game.board
+            .place_structure(Structure::Forest, Direction::North, Block { x: 3, y: 3 })
+            .unwrap();
+        let before = write_map(&game.board);
+        let cash = game.company.cash;
+        apply_confirm_flatten(&mut game);
+        assert_eq!(write_map(&game.board), before);
+        assert_eq!(game.company.cash, cash);
+        assert!(game.history.pop_undo().is_none());
+        assert!(game.pending_flatten.is_none());
     }
 }
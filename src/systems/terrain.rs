@@ -1,99 +1,748 @@
-use crate::state::board::{Board, Vertex};
-use crate::state::game::GameState;
-
-const MIN_HEIGHT: u8 = 0;
-const MAX_HEIGHT: u8 = 6;
+use crate::state::board::{
+    Block, Board, StructureId, StructurePlacement, Vertex, VertexHeight, MAX_HEIGHT, MIN_HEIGHT,
+};
+use crate::state::company::{format_money, LedgerCategory};
+use crate::state::game::{
+    Brush, BrushFalloff, BrushShape, FlattenLevel, GameState, PlayerMode, MAX_BRUSH_RADIUS,
+};
+use crate::state::history::Edit;
+use crate::state::terrain::{StructurePolicy, TerrainError, TerrainPlan, VertexChange};
+use crate::systems::{finance, history};
+use std::collections::HashMap;
 
 pub fn apply_lower_terrain(game: &mut GameState) {
-    if let Some(block) = game.highlighted_block {
-        let v = Vertex {
-            x: block.x,
-            y: block.y,
-        };
-        match lower_vertex(&mut game.board, v) {
-            Ok(()) => println!("Vertex lowered."),
-            Err(()) => println!("Lowering failed."),
+    if let (PlayerMode::RaiseLower { brush }, Some(block)) =
+        (game.player_mode, game.highlighted_block)
+    {
+        match plan_lower(&game.board, block.into(), &brush, game.structure_policy()) {
+            Ok(plan) => match commit_plan(game, &plan) {
+                Ok((edits, bulldozed)) => {
+                    println!(
+                        "Terrain lowered for {} ({} vertices changed).",
+                        format_money(plan.cost()),
+                        plan.changes().len()
+                    );
+                    report_bulldozed(&bulldozed);
+                    game.history.record(Edit::Group(edits));
+                }
+                Err(e) => println!("Lowering failed: {}", e),
+            },
+            Err(e) => println!("Lowering failed: {}", e),
         }
     }
 }
 
 pub fn apply_raise_terrain(game: &mut GameState) {
-    if let Some(block) = game.highlighted_block {
-        let v = Vertex {
-            x: block.x,
-            y: block.y,
+    if let (PlayerMode::RaiseLower { brush }, Some(block)) =
+        (game.player_mode, game.highlighted_block)
+    {
+        match plan_raise(&game.board, block.into(), &brush, game.structure_policy()) {
+            Ok(plan) => match commit_plan(game, &plan) {
+                Ok((edits, bulldozed)) => {
+                    println!(
+                        "Terrain raised for {} ({} vertices changed).",
+                        format_money(plan.cost()),
+                        plan.changes().len()
+                    );
+                    report_bulldozed(&bulldozed);
+                    game.history.record(Edit::Group(edits));
+                }
+                Err(e) => println!("Raising failed: {}", e),
+            },
+            Err(e) => println!("Raising failed: {}", e),
+        }
+    }
+}
+
+/// Checks the company can afford a plan, then carries it out, paying for it and refunding the
+/// structures it bulldozes. Returns the edits made, for the history, and the structures which were
+/// bulldozed.
+pub fn commit_plan(
+    game: &mut GameState,
+    plan: &TerrainPlan,
+) -> Result<(Vec<Edit>, Vec<StructurePlacement>), String> {
+    game.company.check_funds(plan.cost())?;
+    let removed = plan.apply(&mut game.board);
+    let refund = removed
+        .iter()
+        .map(|placement| game.refund(placement.structure()))
+        .sum();
+
+    let mut edits = vec![history::terrain_edit(plan, removed.clone())];
+    edits.push(finance::pay(
+        game,
+        LedgerCategory::Terraforming,
+        -plan.cost(),
+    ));
+    if !removed.is_empty() {
+        edits.push(finance::pay(game, LedgerCategory::Construction, refund));
+    }
+    Ok((edits, removed))
+}
+
+fn report_bulldozed(removed: &[StructurePlacement]) {
+    for placement in removed {
+        println!(
+            "{} at {:?} bulldozed.",
+            placement.structure().name(),
+            placement.origin()
+        );
+    }
+}
+
+pub fn apply_select_flatten(game: &mut GameState) {
+    game.player_mode = PlayerMode::Flatten {
+        anchor: None,
+        level: FlattenLevel::Average,
+    };
+    refresh_flatten_preview(game);
+}
+
+/// The first click picks one corner of the area to level and the second click levels it.
+pub fn apply_flatten(game: &mut GameState) {
+    if let (PlayerMode::Flatten { anchor, level }, Some(block)) =
+        (game.player_mode, game.highlighted_block)
+    {
+        match anchor {
+            None => {
+                game.player_mode = PlayerMode::Flatten {
+                    anchor: Some(block),
+                    level,
+                };
+            }
+            Some(_) => {
+                match plan_flatten_selection(game) {
+                    Some(Ok(plan)) => match commit_plan(game, &plan) {
+                        Ok((edits, bulldozed)) => {
+                            println!(
+                                "Area levelled for {} ({} vertices changed).",
+                                format_money(plan.cost()),
+                                plan.changes().len()
+                            );
+                            report_bulldozed(&bulldozed);
+                            game.history.record(Edit::Group(edits));
+                        }
+                        Err(e) => println!("Levelling failed: {}", e),
+                    },
+                    Some(Err(e)) => println!("Levelling failed: {}", e),
+                    None => {}
+                }
+                game.player_mode = PlayerMode::Flatten {
+                    anchor: None,
+                    level,
+                };
+            }
+        }
+        refresh_flatten_preview(game);
+    }
+}
+
+pub fn apply_cancel_flatten(game: &mut GameState) {
+    if let PlayerMode::Flatten { level, .. } = game.player_mode {
+        game.player_mode = PlayerMode::Flatten {
+            anchor: None,
+            level,
+        };
+        refresh_flatten_preview(game);
+    }
+}
+
+/// Steps through the levels the area can be flattened to, from the area's average height up to
+/// the maximum height.
+pub fn apply_adjust_flatten_level(game: &mut GameState, delta: i8) {
+    if let PlayerMode::Flatten { anchor, level } = game.player_mode {
+        // Average sits just below the lowest fixed height.
+        let step = match level {
+            FlattenLevel::Average => -1,
+            FlattenLevel::Height(h) => h as i32,
+        };
+        let step = (step + delta as i32).clamp(-1, MAX_HEIGHT as i32);
+        let level = if step < 0 {
+            FlattenLevel::Average
+        } else {
+            FlattenLevel::Height(step as VertexHeight)
+        };
+        println!("Levelling to {:?}.", level);
+
+        game.player_mode = PlayerMode::Flatten { anchor, level };
+        refresh_flatten_preview(game);
+    }
+}
+
+/// Recomputes the preview of the area the player is about to level.
+pub fn refresh_flatten_preview(game: &mut GameState) {
+    game.terrain_preview = plan_flatten_selection(game);
+}
+
+fn plan_flatten_selection(game: &GameState) -> Option<Result<TerrainPlan, TerrainError>> {
+    if let (
+        PlayerMode::Flatten {
+            anchor: Some(anchor),
+            level,
+        },
+        Some(block),
+    ) = (game.player_mode, game.highlighted_block)
+    {
+        let (origin, w, h) = anchor.span(block);
+        let height = match level {
+            FlattenLevel::Average => average_height(&game.board, origin, w, h),
+            FlattenLevel::Height(height) => height,
+        };
+        Some(plan_flatten(
+            &game.board,
+            origin,
+            w,
+            h,
+            height,
+            game.structure_policy(),
+        ))
+    } else {
+        None
+    }
+}
+
+pub fn apply_toggle_bulldoze(game: &mut GameState) {
+    game.bulldoze = !game.bulldoze;
+    refresh_flatten_preview(game);
+    if game.bulldoze {
+        println!("Bulldozing on: terraforming will demolish structures in the way.");
+    } else {
+        println!("Bulldozing off.");
+    }
+}
+
+pub fn apply_select_terraform(game: &mut GameState) {
+    if let PlayerMode::RaiseLower { .. } = game.player_mode {
+        return;
+    }
+
+    game.player_mode = PlayerMode::RaiseLower {
+        brush: Brush::new(),
+    };
+}
+
+pub fn apply_resize_brush(game: &mut GameState, delta: i8) {
+    if let PlayerMode::RaiseLower { mut brush } = game.player_mode {
+        let radius = brush.radius as i32 + delta as i32;
+        brush.radius = radius.clamp(0, MAX_BRUSH_RADIUS as i32) as u8;
+        game.player_mode = PlayerMode::RaiseLower { brush };
+    }
+}
+
+pub fn apply_cycle_brush_shape(game: &mut GameState) {
+    if let PlayerMode::RaiseLower { mut brush } = game.player_mode {
+        brush.shape = match brush.shape {
+            BrushShape::Square => BrushShape::Circle,
+            BrushShape::Circle => BrushShape::Square,
         };
-        match raise_vertex(&mut game.board, v) {
-            Ok(()) => println!("Vertex raised."),
-            Err(()) => println!("Raising failed."),
+        game.player_mode = PlayerMode::RaiseLower { brush };
+    }
+}
+
+pub fn apply_toggle_brush_falloff(game: &mut GameState) {
+    if let PlayerMode::RaiseLower { mut brush } = game.player_mode {
+        brush.falloff = match brush.falloff {
+            BrushFalloff::None => BrushFalloff::Smooth,
+            BrushFalloff::Smooth => BrushFalloff::None,
+        };
+        game.player_mode = PlayerMode::RaiseLower { brush };
+    }
+}
+
+/// Works out every vertex change needed to lower the vertices under `brush`, centred on `center`.
+/// Neighbouring vertices are lowered as needed so no two adjacent vertices differ by more than one.
+pub fn plan_lower(
+    board: &Board,
+    center: Vertex,
+    brush: &Brush,
+    policy: StructurePolicy,
+) -> Result<TerrainPlan, TerrainError> {
+    let targets = brush_targets(board, center, brush, |h, strength| {
+        if h > MIN_HEIGHT + strength {
+            h - strength
+        } else {
+            MIN_HEIGHT
         }
+    });
+    if targets.is_empty() {
+        return Err(TerrainError::MinHeight { vertex: center });
     }
+
+    plan(board, &targets, policy)
 }
 
-fn lower_vertex(mut board: &mut Board, vertex: Vertex) -> Result<(), ()> {
-    println!("Trying to lower: {:?}", vertex);
-    let prior_height = board.vertex_height(vertex);
-    if prior_height <= MIN_HEIGHT {
-        println!("Already min.");
-        return Err(());
+/// Works out every vertex change needed to raise the vertices under `brush`, centred on `center`.
+/// Neighbouring vertices are raised as needed so no two adjacent vertices differ by more than one.
+pub fn plan_raise(
+    board: &Board,
+    center: Vertex,
+    brush: &Brush,
+    policy: StructurePolicy,
+) -> Result<TerrainPlan, TerrainError> {
+    let targets = brush_targets(board, center, brush, |h, strength| {
+        h.saturating_add(strength).min(MAX_HEIGHT)
+    });
+    if targets.is_empty() {
+        return Err(TerrainError::MaxHeight { vertex: center });
     }
 
-    // Vertices on the edge of the board cannot be raised or lowered.
-    if vertex.is_edge_vertex() {
-        println!("Is edge vertex.");
-        return Err(());
+    plan(board, &targets, policy)
+}
+
+/// Works out every vertex change needed to level the `w` x `h` blocks starting at `origin` to
+/// `height`. Vertices around the area are moved as needed to keep the slopes leading up to it.
+pub fn plan_flatten(
+    board: &Board,
+    origin: Block,
+    w: u32,
+    h: u32,
+    height: VertexHeight,
+    policy: StructurePolicy,
+) -> Result<TerrainPlan, TerrainError> {
+    if height > MAX_HEIGHT {
+        return Err(TerrainError::MaxHeight {
+            vertex: origin.into(),
+        });
+    }
+
+    let mut targets = Vec::new();
+    for y in origin.y..(origin.y + h + 1) {
+        for x in origin.x..(origin.x + w + 1) {
+            let v = Vertex { x, y };
+            if !board.vertex_on_board(v) {
+                return Err(TerrainError::EdgeVertex { vertex: v });
+            }
+            targets.push((v, height));
+        }
     }
 
-    // Every surrounding vertex must be at most the current height of this vertex in order to lower.
-    // If a surrounding vertex is not the necessary height, lower that vertex first.
-    // This is a recursive operation.
-    for y in (vertex.y - 1)..(vertex.y + 2) {
-        for x in (vertex.x - 1)..(vertex.x + 2) {
+    plan(board, &targets, policy)
+}
+
+/// The mean height of the vertices around the `w` x `h` blocks starting at `origin`, rounded to
+/// the nearest height unit.
+pub fn average_height(board: &Board, origin: Block, w: u32, h: u32) -> VertexHeight {
+    let mut total: u32 = 0;
+    let mut count: u32 = 0;
+    for y in origin.y..(origin.y + h + 1) {
+        for x in origin.x..(origin.x + w + 1) {
             let v = Vertex { x, y };
+            if board.vertex_on_board(v) {
+                total += board.vertex_height(v) as u32;
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        return MIN_HEIGHT;
+    }
+
+    ((total + count / 2) / count) as VertexHeight
+}
+
+/// Target heights for every vertex under the brush which would actually move.
+/// Edge vertices are left out so the brush can still be used right up against the board edge,
+/// except for the centre vertex which must always be movable.
+fn brush_targets<F>(
+    board: &Board,
+    center: Vertex,
+    brush: &Brush,
+    target_height: F,
+) -> Vec<(Vertex, VertexHeight)>
+where
+    F: Fn(VertexHeight, u8) -> VertexHeight,
+{
+    let radius = brush.radius as i32;
+    let mut targets = Vec::new();
+    for dy in -radius..(radius + 1) {
+        for dx in -radius..(radius + 1) {
+            let strength = match brush.strength(dx, dy) {
+                Some(strength) => strength,
+                None => continue,
+            };
+            let v = match neighbour(board, center, dx, dy) {
+                Some(v) => v,
+                None => continue,
+            };
+            if v != center && v.is_edge_vertex(board) {
+                continue;
+            }
+
             let h = board.vertex_height(v);
-            // This will skip the current vertex.
-            if h > prior_height {
-                // TODO (toby): Avoid making any changes if the prerequisites or unsatisfied
-                // rather than bailing out part-way through.
-                lower_vertex(&mut board, v)?;
+            let target = target_height(h, strength);
+            if target != h {
+                targets.push((v, target));
             }
         }
     }
 
-    board.set_vertex_height(vertex, prior_height - 1);
-    Ok(())
+    targets
 }
 
-fn raise_vertex(mut board: &mut Board, vertex: Vertex) -> Result<(), ()> {
-    println!("Trying to raise: {:?}", vertex);
-    let prior_height = board.vertex_height(vertex);
-    if prior_height >= MAX_HEIGHT {
-        println!("Already max.");
-        return Err(());
+/// Moves each target vertex to its requested height, then cascades outward to every neighbour
+/// which would otherwise differ from an adjacent vertex by more than one height unit.
+fn plan(
+    board: &Board,
+    targets: &[(Vertex, VertexHeight)],
+    policy: StructurePolicy,
+) -> Result<TerrainPlan, TerrainError> {
+    let mut heights: HashMap<Vertex, VertexHeight> = HashMap::new();
+    let mut demolitions: Vec<StructureId> = Vec::new();
+    let mut pending: Vec<Vertex> = Vec::new();
+
+    for &(vertex, height) in targets.iter() {
+        if height != board.vertex_height(vertex) {
+            heights.insert(vertex, height);
+            pending.push(vertex);
+        }
+    }
+
+    while let Some(vertex) = pending.pop() {
+        let height = heights[&vertex];
+        check_vertex(board, vertex, height)?;
+        for structure in structures_on_vertex(board, vertex) {
+            match policy {
+                StructurePolicy::Protect => {
+                    return Err(TerrainError::StructureInTheWay { vertex, structure });
+                }
+                StructurePolicy::Bulldoze => {
+                    if !demolitions.contains(&structure) {
+                        demolitions.push(structure);
+                    }
+                }
+            }
+        }
+
+        for y in (vertex.y - 1)..(vertex.y + 2) {
+            for x in (vertex.x - 1)..(vertex.x + 2) {
+                let v = Vertex { x, y };
+                let h = match heights.get(&v) {
+                    Some(&h) => h,
+                    None => board.vertex_height(v),
+                };
+                // This will skip the current vertex.
+                let required = if h + 1 < height {
+                    height - 1
+                } else if h > height + 1 {
+                    height + 1
+                } else {
+                    continue;
+                };
+                heights.insert(v, required);
+                pending.push(v);
+            }
+        }
     }
 
+    let mut changes: Vec<VertexChange> = heights
+        .into_iter()
+        .map(|(vertex, to)| VertexChange {
+            vertex,
+            from: board.vertex_height(vertex),
+            to,
+        })
+        .filter(|change| change.from != change.to)
+        .collect();
+    changes.sort_by_key(|change| (change.vertex.y, change.vertex.x));
+
+    Ok(TerrainPlan::new(changes, demolitions))
+}
+
+fn check_vertex(board: &Board, vertex: Vertex, height: VertexHeight) -> Result<(), TerrainError> {
     // Vertices on the edge of the board cannot be raised or lowered.
-    if vertex.is_edge_vertex() {
-        println!("Is edge vertex.");
-        return Err(());
+    if vertex.is_edge_vertex(board) {
+        return Err(TerrainError::EdgeVertex { vertex });
+    }
+
+    if height > MAX_HEIGHT {
+        return Err(TerrainError::MaxHeight { vertex });
+    }
+
+    Ok(())
+}
+
+/// Structures standing on any of the four blocks which share the vertex.
+fn structures_on_vertex(board: &Board, vertex: Vertex) -> Vec<StructureId> {
+    let mut structures = Vec::new();
+    for y in (vertex.y - 1)..(vertex.y + 1) {
+        for x in (vertex.x - 1)..(vertex.x + 1) {
+            if let Some(id) = board.structure_at(Block { x, y }) {
+                if !structures.contains(&id) {
+                    structures.push(id);
+                }
+            }
+        }
     }
 
-    // Every surrounding vertex must be at least the current height of this vertex in order to raise.
-    // If a surrounding vertex is not the necessary height, raise that vertex first.
-    // This is a recursive operation.
-    for y in (vertex.y - 1)..(vertex.y + 2) {
-        for x in (vertex.x - 1)..(vertex.x + 2) {
+    structures
+}
+
+/// Lowers any vertex which is more than one height unit above one of its neighbours, so the whole
+/// board satisfies the same slope rule that `plan_raise` and `plan_lower` maintain.
+/// Vertices are only ever lowered, never raised.
+pub fn limit_slopes(board: &mut Board) {
+    let width = board.width();
+    let height = board.height();
+
+    // A forward pass bounds each vertex by the neighbours before it in raster order and a backward
+    // pass by the neighbours after it. Together they bound every vertex by all eight neighbours.
+    for y in 0..(height + 1) {
+        for x in 0..(width + 1) {
             let v = Vertex { x, y };
-            let h = board.vertex_height(v);
-            // This will skip the current vertex.
-            if h < prior_height {
-                // TODO (toby): Avoid making any changes if the prerequisites or unsatisfied
-                // rather than bailing out part-way through.
-                raise_vertex(&mut board, v)?;
+            let mut h = board.vertex_height(v);
+            for &(dx, dy) in [(-1, 0), (-1, -1), (0, -1), (1, -1)].iter() {
+                if let Some(n) = neighbour(board, v, dx, dy) {
+                    h = h.min(board.vertex_height(n) + 1);
+                }
             }
+            board.set_vertex_height(v, h);
         }
     }
 
-    board.set_vertex_height(vertex, prior_height + 1);
-    Ok(())
+    for y in (0..(height + 1)).rev() {
+        for x in (0..(width + 1)).rev() {
+            let v = Vertex { x, y };
+            let mut h = board.vertex_height(v);
+            for &(dx, dy) in [(1, 0), (1, 1), (0, 1), (-1, 1)].iter() {
+                if let Some(n) = neighbour(board, v, dx, dy) {
+                    h = h.min(board.vertex_height(n) + 1);
+                }
+            }
+            board.set_vertex_height(v, h);
+        }
+    }
+}
+
+fn neighbour(board: &Board, v: Vertex, dx: i32, dy: i32) -> Option<Vertex> {
+    let x = v.x as i32 + dx;
+    let y = v.y as i32 + dy;
+    if x < 0 || y < 0 {
+        return None;
+    }
+
+    let n = Vertex {
+        x: x as u32,
+        y: y as u32,
+    };
+    if board.vertex_on_board(n) {
+        Some(n)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::board::{Direction, Structure};
+    use crate::state::terrain::COST_PER_HEIGHT_CHANGE;
+    use crate::storage::text_map::write_map;
+
+    const CENTER: Vertex = Vertex { x: 5, y: 5 };
+
+    /// A 12x12 board of low land with a hill peaking at height 4 in the middle, stepping down by one
+    /// per ring to the surrounding land three vertices out, and a forest on that third ring.
+    fn hill() -> GameState {
+        let mut board = Board::new(12, 12);
+        for y in 1..12 {
+            for x in 1..12 {
+                let v = Vertex { x, y };
+                let distance = (x as i32 - 5).abs().max((y as i32 - 5).abs());
+                board.set_vertex_height(v, (4 - distance).max(1) as VertexHeight);
+            }
+        }
+        board
+            .place_structure(Structure::Forest, Direction::North, Block { x: 8, y: 5 })
+            .unwrap();
+
+        let mut game = GameState::new(board);
+        game.player_mode = PlayerMode::RaiseLower {
+            brush: Brush::new(),
+        };
+        game.highlighted_block = Some(Block { x: 5, y: 5 });
+        game
+    }
+
+    /// Raises the highlighted vertex and checks the board and company are exactly as they were.
+    fn assert_raise_refused(game: &mut GameState) {
+        let board = write_map(&game.board);
+        let cash = game.company.cash;
+        apply_raise_terrain(game);
+        assert_eq!(write_map(&game.board), board);
+        assert_eq!(game.company.cash, cash);
+        assert!(game.history.pop_undo().is_none());
+    }
+
+    #[test]
+    fn cascades_reaching_a_structure_change_nothing() {
+        let mut game = hill();
+        let brush = Brush::new();
+        match plan_raise(&game.board, CENTER, &brush, StructurePolicy::Protect) {
+            Err(TerrainError::StructureInTheWay { vertex, .. }) => {
+                assert_eq!(vertex.x.max(vertex.y), 8)
+            }
+            other => panic!("expected the forest to block the raise, got {:?}", other),
+        }
+        assert_raise_refused(&mut game);
+    }
+
+    #[test]
+    fn cascades_reaching_the_edge_change_nothing() {
+        let mut game = hill();
+        let brush = Brush {
+            radius: 2,
+            shape: BrushShape::Square,
+            falloff: BrushFalloff::Smooth,
+        };
+        game.player_mode = PlayerMode::RaiseLower { brush };
+        game.highlighted_block = Some(Block { x: 2, y: 9 });
+        let error = plan_raise(
+            &game.board,
+            Vertex { x: 2, y: 9 },
+            &brush,
+            StructurePolicy::Bulldoze,
+        );
+        assert!(matches!(error, Err(TerrainError::EdgeVertex { .. })));
+        assert_raise_refused(&mut game);
+    }
+
+    #[test]
+    fn raises_beyond_the_maximum_height_change_nothing() {
+        let mut game = hill();
+        game.board.set_vertex_height(CENTER, MAX_HEIGHT);
+        let targets = [(Vertex { x: 4, y: 4 }, 4), (CENTER, MAX_HEIGHT + 1)];
+        let error = plan(&game.board, &targets, StructurePolicy::Bulldoze);
+        assert_eq!(error, Err(TerrainError::MaxHeight { vertex: CENTER }));
+        assert_raise_refused(&mut game);
+    }
+
+    #[test]
+    fn successful_cascades_apply_every_change() {
+        let mut game = hill();
+        game.bulldoze = true;
+        let plan = plan_raise(
+            &game.board,
+            CENTER,
+            &Brush::new(),
+            StructurePolicy::Bulldoze,
+        )
+        .unwrap();
+        // The peak, and the 8, 16 and 24 vertices of the three rings around it.
+        assert_eq!(plan.changes().len(), 1 + 8 + 16 + 24);
+        assert_eq!(plan.cost(), 49 * COST_PER_HEIGHT_CHANGE);
+
+        apply_raise_terrain(&mut game);
+        for change in plan.changes() {
+            assert_eq!(change.to, change.from + 1);
+            assert_eq!(game.board.vertex_height(change.vertex), change.to);
+        }
+        assert_eq!(game.board.vertex_height(CENTER), 5);
+        assert_eq!(game.board.vertex_height(Vertex { x: 9, y: 5 }), 1);
+        assert_eq!(game.board.structures().count(), 0);
+    }
+
+    #[test]
+    fn bulldozing_plans_remove_and_refund_the_structures_in_the_way() {
+        let mut game = hill();
+        let plan = plan_raise(
+            &game.board,
+            CENTER,
+            &Brush::new(),
+            StructurePolicy::Bulldoze,
+        )
+        .unwrap();
+        let cash = game.company.cash;
+        let (edits, removed) = commit_plan(&mut game, &plan).unwrap();
+
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].structure() == Structure::Forest);
+        assert_eq!(removed[0].origin(), Block { x: 8, y: 5 });
+        assert!(game.board.structure_at(Block { x: 8, y: 5 }).is_none());
+        // The terrain edit, the payment and the refund.
+        assert_eq!(edits.len(), 3);
+        assert_eq!(
+            game.company.cash,
+            cash - plan.cost() + game.refund(Structure::Forest)
+        );
+    }
+
+    /// Puts the hill into flatten mode with the area from `anchor` to `corner` selected.
+    fn select_flatten(game: &mut GameState, anchor: Block, corner: Block, level: FlattenLevel) {
+        game.player_mode = PlayerMode::Flatten {
+            anchor: Some(anchor),
+            level,
+        };
+        game.highlighted_block = Some(corner);
+        refresh_flatten_preview(game);
+    }
+
+    #[test]
+    fn average_heights_round_to_the_nearest_unit() {
+        let game = hill();
+        // The peak at 4 and the eight vertices around it at 3.
+        assert_eq!(average_height(&game.board, Block { x: 4, y: 4 }, 2, 2), 3);
+        // 17 over six vertices rounds up, and 1 over four vertices on the shore rounds down.
+        assert_eq!(average_height(&game.board, Block { x: 5, y: 5 }, 2, 1), 3);
+        assert_eq!(average_height(&game.board, Block { x: 0, y: 0 }, 1, 1), 0);
+    }
+
+    #[test]
+    fn flattening_levels_the_area_at_its_average_for_the_previewed_cost() {
+        let mut game = hill();
+        select_flatten(
+            &mut game,
+            Block { x: 4, y: 4 },
+            Block { x: 5, y: 5 },
+            FlattenLevel::Average,
+        );
+        let preview = match &game.terrain_preview {
+            Some(Ok(plan)) => plan.clone(),
+            other => panic!("expected a flatten preview, got {:?}", other),
+        };
+        let direct = plan_flatten(
+            &game.board,
+            Block { x: 4, y: 4 },
+            2,
+            2,
+            3,
+            StructurePolicy::Protect,
+        )
+        .unwrap();
+        assert_eq!(preview.changes(), direct.changes());
+
+        let cash = game.company.cash;
+        apply_flatten(&mut game);
+        assert_eq!(game.company.cash, cash - preview.cost());
+        assert_eq!(preview.cost(), COST_PER_HEIGHT_CHANGE);
+        for y in 4..=6 {
+            for x in 4..=6 {
+                assert_eq!(game.board.vertex_height(Vertex { x, y }), 3);
+            }
+        }
+        assert!(game.history.pop_undo().is_some());
+    }
+
+    #[test]
+    fn flattening_into_a_structure_changes_nothing() {
+        let mut game = hill();
+        select_flatten(
+            &mut game,
+            Block { x: 5, y: 5 },
+            Block { x: 8, y: 5 },
+            FlattenLevel::Height(4),
+        );
+        assert!(matches!(
+            game.terrain_preview,
+            Some(Err(TerrainError::StructureInTheWay { .. }))
+        ));
+
+        let board = write_map(&game.board);
+        let cash = game.company.cash;
+        apply_flatten(&mut game);
+        assert_eq!(write_map(&game.board), board);
+        assert_eq!(game.company.cash, cash);
+        assert!(game.history.pop_undo().is_none());
+        assert_eq!(game.board.structures().count(), 1);
+    }
 }
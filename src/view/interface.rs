@@ -1,4 +1,7 @@
+use super::screens::bankruptcy::BankruptcyScreen;
 use super::screens::building::BuyBuildingScreen;
+use super::screens::finances::FinancesScreen;
+use super::screens::load::LoadScreen;
 use super::sidebar::Sidebar;
 use super::text::DynamicText;
 use super::viewport::Viewport;
@@ -7,14 +10,20 @@ use super::{
     TEXT_HEIGHT,
 };
 use crate::action::GameAction;
+use crate::state::calendar::GameSpeed;
 use crate::state::game::GameState;
 use crate::state::menu::building::BuyBuildingScreenState;
+use crate::state::menu::finances::FinancesScreenState;
+use crate::state::menu::load::LoadScreenState;
 use sdl2::render::{Canvas, TextureCreator};
 use sdl2::video::Window;
 use std::rc::Rc;
 
 pub struct Interface {
+    bankruptcy_screen: BankruptcyScreen,
     buy_building_screen: BuyBuildingScreen,
+    finances_screen: FinancesScreen,
+    load_screen: LoadScreen,
     viewport: Viewport,
     screen: ScreenState,
     sidebar: Sidebar,
@@ -22,17 +31,20 @@ pub struct Interface {
 
 impl Interface {
     pub fn new<T>(texture_creator: TextureCreator<T>, screen: ScreenState) -> Self {
-        let dynamic_text = DynamicText::new(&texture_creator, screen);
+        let dynamic_text = Rc::new(DynamicText::new(&texture_creator, screen));
 
         Self {
+            bankruptcy_screen: BankruptcyScreen::new(Rc::clone(&dynamic_text), screen),
             buy_building_screen: BuyBuildingScreen::new(
                 &texture_creator,
-                Rc::new(dynamic_text),
+                Rc::clone(&dynamic_text),
                 screen,
             ),
+            finances_screen: FinancesScreen::new(Rc::clone(&dynamic_text), screen),
+            load_screen: LoadScreen::new(Rc::clone(&dynamic_text), screen),
             viewport: Viewport::new(screen),
             screen,
-            sidebar: Sidebar::new(&texture_creator, screen),
+            sidebar: Sidebar::new(&texture_creator, dynamic_text, screen),
         }
     }
 
@@ -41,6 +53,23 @@ impl Interface {
         game: &GameState,
         player_action: PlayerInteraction,
     ) -> Option<GameAction> {
+        if let LoadScreenState::Visible { naming, .. } = &game.load_screen {
+            return self
+                .load_screen
+                .map_player_interaction(naming.is_some(), player_action);
+        }
+        if game.company.bankrupt {
+            return self.bankruptcy_screen.map_player_interaction(player_action);
+        }
+        if game.finances_screen == FinancesScreenState::Visible {
+            return self.finances_screen.map_player_interaction(player_action);
+        }
+        if game.buy_building_screen != BuyBuildingScreenState::Hidden {
+            return self
+                .buy_building_screen
+                .map_player_interaction(game, player_action);
+        }
+
         match player_action {
             PlayerInteraction::CursorMove { x, y } => match self.window_panel(x, y) {
                 WindowPanel::Sidebar => self.sidebar.cursor_move_action(x, y),
@@ -54,9 +83,47 @@ impl Interface {
                 WindowPanel::Sidebar => None,
                 WindowPanel::Viewport => self.viewport.right_click_action(game),
             },
-            PlayerInteraction::KeyPress {
-                key: KeyboardKey::Space,
-            } => self.viewport.spacebar_action(game),
+            PlayerInteraction::WindowScroll { x, y, delta } => match self.window_panel(x, y) {
+                WindowPanel::Sidebar => None,
+                WindowPanel::Viewport => self.viewport.scroll_action(game, delta),
+            },
+            PlayerInteraction::TextInput { .. } => None,
+            PlayerInteraction::KeyPress { key } => match key {
+                KeyboardKey::Space => self.viewport.spacebar_action(game),
+                KeyboardKey::Enter => Some(GameAction::ConfirmFlatten),
+                KeyboardKey::Escape => Some(GameAction::Quit),
+                KeyboardKey::Backspace
+                | KeyboardKey::Up
+                | KeyboardKey::Down
+                | KeyboardKey::N
+                | KeyboardKey::R => None,
+                KeyboardKey::Undo => Some(GameAction::Undo),
+                KeyboardKey::Redo => Some(GameAction::Redo),
+                KeyboardKey::LeftBracket => self.viewport.adjust_action(game, -1),
+                KeyboardKey::RightBracket => self.viewport.adjust_action(game, 1),
+                KeyboardKey::B => Some(GameAction::CycleBrushShape),
+                KeyboardKey::E => Some(GameAction::ExportHeightmap),
+                KeyboardKey::F => Some(GameAction::ToggleBrushFalloff),
+                KeyboardKey::L => Some(GameAction::SelectFlatten),
+                KeyboardKey::M => Some(GameAction::ExportMap),
+                KeyboardKey::O => Some(GameAction::OpenLoadScreen),
+                KeyboardKey::P => Some(GameAction::TogglePause),
+                KeyboardKey::S => Some(GameAction::SaveGame),
+                KeyboardKey::T => Some(GameAction::SelectTerraform),
+                KeyboardKey::X => Some(GameAction::ToggleBulldoze),
+                KeyboardKey::Num1 => Some(GameAction::SetSpeed {
+                    speed: GameSpeed::X1,
+                }),
+                KeyboardKey::Num2 => Some(GameAction::SetSpeed {
+                    speed: GameSpeed::X2,
+                }),
+                KeyboardKey::Num3 => Some(GameAction::SetSpeed {
+                    speed: GameSpeed::X4,
+                }),
+                KeyboardKey::Num4 => Some(GameAction::SetSpeed {
+                    speed: GameSpeed::X8,
+                }),
+            },
         }
     }
 
@@ -66,13 +133,24 @@ impl Interface {
 
         self.viewport.render(canvas, &game)?;
         self.sidebar.render(canvas, &game)?;
-        if let BuyBuildingScreenState::Visible {
-            selected_building,
-            selected_category,
-        } = game.buy_building_screen
-        {
+        if game.buy_building_screen != BuyBuildingScreenState::Hidden {
             self.buy_building_screen.render(canvas, game)?;
         }
+        if game.finances_screen == FinancesScreenState::Visible {
+            self.finances_screen.render(canvas, game)?;
+        }
+        if game.company.bankrupt {
+            self.bankruptcy_screen.render(canvas, game)?;
+        }
+        if let LoadScreenState::Visible {
+            slots,
+            selected,
+            naming,
+        } = &game.load_screen
+        {
+            self.load_screen
+                .render(canvas, slots, *selected, naming.as_deref())?;
+        }
 
         canvas.present();
 
@@ -3,6 +3,7 @@ mod screens;
 mod sidebar;
 pub mod text;
 mod viewport;
+pub mod window;
 
 pub use interface::Interface;
 
@@ -18,15 +19,62 @@ pub(self) const DIALOG_HEIGHT: u32 = 480;
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum PlayerInteraction {
-    CursorMove { x: i32, y: i32 },
-    WindowLeftClick { x: i32, y: i32 },
-    WindowRightClick { x: i32, y: i32 },
-    KeyPress { key: KeyboardKey },
+    CursorMove {
+        x: i32,
+        y: i32,
+    },
+    WindowLeftClick {
+        x: i32,
+        y: i32,
+    },
+    WindowRightClick {
+        x: i32,
+        y: i32,
+    },
+    WindowScroll {
+        x: i32,
+        y: i32,
+        delta: i32,
+    },
+    KeyPress {
+        key: KeyboardKey,
+    },
+    /// A character typed, as opposed to a key pressed, for screens which take text.
+    TextInput {
+        character: char,
+    },
 }
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum KeyboardKey {
     Space,
+    Enter,
+    Escape,
+    Backspace,
+    Up,
+    Down,
+    /// Ctrl+Z.
+    Undo,
+    /// Ctrl+Y or Ctrl+Shift+Z.
+    Redo,
+    LeftBracket,
+    RightBracket,
+    B,
+    E,
+    F,
+    L,
+    M,
+    N,
+    O,
+    P,
+    R,
+    S,
+    T,
+    X,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -0,0 +1,85 @@
+use crate::action::GameAction;
+use crate::state::company::{format_money, BANKRUPTCY_DAYS};
+use crate::state::game::GameState;
+use crate::view::text::DynamicText;
+use crate::view::{
+    KeyboardKey, PlayerInteraction, ScreenState, DIALOG_HEIGHT, DIALOG_WIDTH, TEXT_HEIGHT,
+};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::rc::Rc;
+
+const COLOR_BACKGROUND: (u8, u8, u8) = (82, 82, 82);
+const COLOR_DIALOG: (u8, u8, u8) = (132, 132, 123);
+const LINE_SPACING: u32 = 4;
+const MARGIN: u32 = 16;
+
+/// Shown once the company has gone bankrupt. The game is over, so the player can only load a
+/// saved game or quit.
+pub struct BankruptcyScreen {
+    screen: ScreenState,
+    dynamic_text: Rc<DynamicText>,
+}
+
+impl BankruptcyScreen {
+    pub fn new(dynamic_text: Rc<DynamicText>, screen: ScreenState) -> Self {
+        Self {
+            dynamic_text,
+            screen,
+        }
+    }
+
+    pub fn map_player_interaction(&self, player_action: PlayerInteraction) -> Option<GameAction> {
+        match player_action {
+            PlayerInteraction::KeyPress {
+                key: KeyboardKey::L,
+            } => Some(GameAction::OpenLoadScreen),
+            PlayerInteraction::KeyPress {
+                key: KeyboardKey::Escape,
+            } => Some(GameAction::Quit),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>, game: &GameState) -> Result<(), String> {
+        let (screen_width, screen_height) = self.screen.size();
+
+        canvas.set_draw_color(Color::from(COLOR_BACKGROUND));
+        let screen_rect = Rect::new(0, 0, screen_width, screen_height);
+        canvas.fill_rect(screen_rect)?;
+
+        let dialog_width = self.screen.scale_x(DIALOG_WIDTH as i32) as u32;
+        let dialog_height = self.screen.scale_y(DIALOG_HEIGHT as i32) as u32;
+        let dialog_rect = Rect::from_center(screen_rect.center(), dialog_width, dialog_height);
+        canvas.set_draw_color(Color::from(COLOR_DIALOG));
+        canvas.fill_rect(dialog_rect)?;
+
+        let lines = [
+            String::from("Bankrupt"),
+            String::from("L: load a saved game   Esc: quit"),
+            String::new(),
+            format!(
+                "The company's cash stayed below zero for {} days.",
+                BANKRUPTCY_DAYS
+            ),
+            format!("It went under on {}.", game.date),
+            String::new(),
+            format!("Cash: {}", format_money(game.company.cash)),
+            format!("Loan: {}", format_money(game.company.loan)),
+        ];
+
+        let margin = self.screen.scale_y(MARGIN as i32);
+        let line_spacing = self.screen.scale_y(LINE_SPACING as i32);
+        let line_height = self.screen.scale_y(TEXT_HEIGHT as i32) + line_spacing;
+        let left = dialog_rect.left() + self.screen.scale_x(MARGIN as i32);
+        for (idx, line) in lines.iter().enumerate() {
+            let top = dialog_rect.top() + margin + line_height * idx as i32;
+            self.dynamic_text
+                .print(canvas, line, Point::new(left, top), false)?;
+        }
+
+        Ok(())
+    }
+}
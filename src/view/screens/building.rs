@@ -1,21 +1,29 @@
+use crate::action::GameAction;
+use crate::state::company::format_money;
 use crate::state::game::GameState;
-use crate::state::menu::building::{BuyBuildingScreenState, Category};
+use crate::state::menu::building::{BuyBuildingButton, BuyBuildingScreenState, CATEGORIES};
 use crate::view::text::DynamicText;
-use crate::view::{ScreenState, DIALOG_HEIGHT, DIALOG_WIDTH, TEXT_HEIGHT};
+use crate::view::{
+    KeyboardKey, PlayerInteraction, ScreenState, DIALOG_HEIGHT, DIALOG_WIDTH, TEXT_HEIGHT,
+};
 use sdl2::image::LoadTexture;
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
-use sdl2::render::{Canvas, Texture, TextureCreator, TextureQuery};
+use sdl2::render::{Canvas, Texture, TextureCreator};
 use sdl2::video::Window;
 use std::rc::Rc;
 
 const COLOR_BACKGROUND: (u8, u8, u8) = (82, 82, 82);
 const COLOR_HIGHLIGHT: (u8, u8, u8) = (255, 255, 255);
-const COLOR_TEXT: (u8, u8, u8) = (255, 255, 255);
-const COLOR_TEXT_HIGHLIGHTED: (u8, u8, u8) = (0, 0, 0);
+const COLOR_HOVER: (u8, u8, u8) = (132, 132, 123);
 const ASSET_DIMENSIONS: (u32, u32) = (2560, 1920);
 const BUILDING_CATEGORIES_OFFSET: (u32, u32) = (1656, 378);
 const BUILDING_SELECTION_OFFSET: (u32, u32) = (1656, 680);
+const BUILDING_INFO_OFFSET: (u32, u32) = (1656, 1512);
+/// The Buy and Close buttons sit side by side on this line of the info box.
+const INFO_BUTTON_LINE: i32 = 3;
+const INFO_BUTTON_WIDTH: u32 = 300;
+const INFO_BUTTON_SPACING: u32 = 100;
 const LINE_SPACING: u32 = 4;
 const TEXT_AREA_WIDTH: u32 = 800;
 
@@ -41,80 +49,212 @@ impl BuyBuildingScreen {
         }
     }
 
+    pub fn map_player_interaction(
+        &self,
+        game: &GameState,
+        player_action: PlayerInteraction,
+    ) -> Option<GameAction> {
+        match player_action {
+            PlayerInteraction::CursorMove { x, y } => Some(GameAction::BuyBuildingHover {
+                button: self.button_under_cursor(game, x, y),
+            }),
+            PlayerInteraction::WindowLeftClick { x, y } => {
+                match self.button_under_cursor(game, x, y)? {
+                    BuyBuildingButton::Category(category) => {
+                        Some(GameAction::SelectBuildingCategory { category })
+                    }
+                    BuyBuildingButton::Building(building) => {
+                        Some(GameAction::SelectBuilding { building })
+                    }
+                    BuyBuildingButton::Buy => Some(GameAction::BuyBuilding),
+                    BuyBuildingButton::Close => Some(GameAction::CloseBuyBuildingScreen),
+                }
+            }
+            PlayerInteraction::KeyPress { key } => match key {
+                KeyboardKey::Escape => Some(GameAction::CloseBuyBuildingScreen),
+                KeyboardKey::Enter => Some(GameAction::BuyBuilding),
+                KeyboardKey::Up => Self::adjacent_building_action(game, -1),
+                KeyboardKey::Down => Self::adjacent_building_action(game, 1),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     pub fn render(&self, canvas: &mut Canvas<Window>, game: &GameState) -> Result<(), String> {
+        let (selected_building, selected_category, highlighted) = match game.buy_building_screen {
+            BuyBuildingScreenState::Visible {
+                selected_building,
+                selected_category,
+                highlighted,
+            } => (selected_building, selected_category, highlighted),
+            BuyBuildingScreenState::Hidden => return Ok(()),
+        };
         let (screen_width, screen_height) = self.screen.size();
 
         canvas.set_draw_color(Color::from(COLOR_BACKGROUND));
         let screen_rect = Rect::new(0, 0, screen_width, screen_height);
         canvas.fill_rect(screen_rect)?;
 
-        let dialog_width = self.screen.scale_x(DIALOG_WIDTH as i32) as u32;
-        let dialog_height = self.screen.scale_y(DIALOG_HEIGHT as i32) as u32;
-
-        let screen_center = screen_rect.center();
-        let dialog_rect = Rect::from_center(screen_center, dialog_width, dialog_height);
-
+        let dialog_rect = self.dialog_rect();
         let texture = &self.textures[0];
         canvas.copy(texture, None, Some(dialog_rect))?;
 
-        let (asset_width, asset_height) = ASSET_DIMENSIONS;
-        let scale = dialog_width as f32 / asset_width as f32;
-
-        let (cat_offset_x, cat_offset_y) = BUILDING_CATEGORIES_OFFSET;
-        let cat_offset_x = (cat_offset_x as f32 * scale) as i32;
-        let cat_offset_y = (cat_offset_y as f32 * scale) as i32;
         let line_spacing = self.screen.scale_y(LINE_SPACING as i32);
-        let line_height = self.screen.scale_y(TEXT_HEIGHT as i32) + line_spacing;
-        let line_width = (TEXT_AREA_WIDTH as f32 * scale) as i32;
+        for (button, rect) in self.buttons(game) {
+            // List entries light up when selected, while Buy and Close are always filled in and
+            // light up under the cursor.
+            let hovered = highlighted == Some(button);
+            let (label, selected, filled) = match button {
+                BuyBuildingButton::Category(category) => {
+                    (category.name(), category == selected_category, hovered)
+                }
+                BuyBuildingButton::Building(building) => {
+                    (building.name(), building == selected_building, hovered)
+                }
+                BuyBuildingButton::Buy => ("Buy", hovered, true),
+                BuyBuildingButton::Close => ("Close", hovered, true),
+            };
 
-        let selected_category = match game.buy_building_screen {
-            BuyBuildingScreenState::Visible {
-                selected_building,
-                selected_category,
-            } => Some(selected_category),
-            BuyBuildingScreenState::Hidden => None,
-        };
-        let mut line_num = 0;
-        for &cat in [
-            Category::Terminals,
-            Category::Production,
-            Category::Retail,
-            Category::CityBuildings,
-        ]
-        .iter()
-        {
-            let highlight = selected_category == Some(cat);
-            let line_left = dialog_rect.left() + cat_offset_x;
-            let line_top = dialog_rect.top() + cat_offset_y + line_height * line_num;
-
-            if highlight {
-                // Draw background.
+            if selected {
                 canvas.set_draw_color(Color::from(COLOR_HIGHLIGHT));
-                canvas.fill_rect(Rect::new(
-                    line_left,
-                    line_top - line_spacing / 2,
-                    line_width as u32,
-                    line_height as u32,
-                ))?;
+                canvas.fill_rect(rect)?;
+            } else if filled {
+                canvas.set_draw_color(Color::from(COLOR_HOVER));
+                canvas.fill_rect(rect)?;
             }
             self.dynamic_text.print(
                 canvas,
-                &Self::display_name(cat),
-                Point::new(line_left, line_top),
-                highlight,
+                label,
+                Point::new(rect.left(), rect.top() + line_spacing / 2),
+                selected,
+            )?;
+        }
+
+        let structure = selected_building.structure();
+        let (width, height) = structure.size();
+        let info = [
+            selected_building.name().to_string(),
+            format!("Cost: {}", format_money(structure.cost())),
+            format!("Size: {}x{}", width, height),
+        ];
+        for (line, text) in info.iter().enumerate() {
+            self.dynamic_text.print(
+                canvas,
+                text,
+                self.line_origin(BUILDING_INFO_OFFSET, 0, line as i32),
+                false,
             )?;
-            line_num += 1;
         }
 
         Ok(())
     }
 
-    fn display_name(cat: Category) -> String {
-        match cat {
-            Category::Terminals => String::from("Terminals"),
-            Category::Production => String::from("Production"),
-            Category::Retail => String::from("Retail"),
-            Category::CityBuildings => String::from("Buildings (city)"),
+    fn button_under_cursor(&self, game: &GameState, x: i32, y: i32) -> Option<BuyBuildingButton> {
+        self.buttons(game)
+            .into_iter()
+            .find(|(_, rect)| rect.contains_point(Point::new(x, y)))
+            .map(|(button, _)| button)
+    }
+
+    /// Every button on the screen and where it is: the categories, the buildings in the selected
+    /// category, then Buy and Close.
+    fn buttons(&self, game: &GameState) -> Vec<(BuyBuildingButton, Rect)> {
+        let selected_category = match game.buy_building_screen {
+            BuyBuildingScreenState::Visible {
+                selected_category, ..
+            } => selected_category,
+            BuyBuildingScreenState::Hidden => return Vec::new(),
+        };
+
+        let line_width = self.scale_asset(TEXT_AREA_WIDTH);
+        let mut buttons = Vec::new();
+        for (line, &category) in CATEGORIES.iter().enumerate() {
+            let origin = self.line_origin(BUILDING_CATEGORIES_OFFSET, 0, line as i32);
+            buttons.push((
+                BuyBuildingButton::Category(category),
+                self.line_rect(origin, line_width),
+            ));
+        }
+        for (line, &building) in selected_category.buildings().iter().enumerate() {
+            let origin = self.line_origin(BUILDING_SELECTION_OFFSET, 0, line as i32);
+            buttons.push((
+                BuyBuildingButton::Building(building),
+                self.line_rect(origin, line_width),
+            ));
+        }
+
+        let button_width = self.scale_asset(INFO_BUTTON_WIDTH);
+        let button_step = INFO_BUTTON_WIDTH + INFO_BUTTON_SPACING;
+        for (idx, &button) in [BuyBuildingButton::Buy, BuyBuildingButton::Close]
+            .iter()
+            .enumerate()
+        {
+            let origin = self.line_origin(
+                BUILDING_INFO_OFFSET,
+                button_step * idx as u32,
+                INFO_BUTTON_LINE,
+            );
+            buttons.push((button, self.line_rect(origin, button_width)));
+        }
+        buttons
+    }
+
+    /// Where the text on a line of one of the art's boxes starts. The box is placed by its offset
+    /// into the art, and the text by a further distance across the art and a number of lines down.
+    fn line_origin(&self, offset: (u32, u32), across: u32, line: i32) -> Point {
+        let dialog_rect = self.dialog_rect();
+        let line_spacing = self.screen.scale_y(LINE_SPACING as i32);
+        let line_height = self.screen.scale_y(TEXT_HEIGHT as i32) + line_spacing;
+        let (offset_x, offset_y) = offset;
+        Point::new(
+            dialog_rect.left() + self.scale_asset(offset_x + across) as i32,
+            dialog_rect.top() + self.scale_asset(offset_y) as i32 + line_height * line,
+        )
+    }
+
+    /// The area taken up by a line of text starting at `origin`, with half the spacing between
+    /// lines above and below it.
+    fn line_rect(&self, origin: Point, width: u32) -> Rect {
+        let line_spacing = self.screen.scale_y(LINE_SPACING as i32);
+        let line_height = self.screen.scale_y(TEXT_HEIGHT as i32) + line_spacing;
+        Rect::new(
+            origin.x(),
+            origin.y() - line_spacing / 2,
+            width,
+            line_height as u32,
+        )
+    }
+
+    fn dialog_rect(&self) -> Rect {
+        let (screen_width, screen_height) = self.screen.size();
+        let screen_rect = Rect::new(0, 0, screen_width, screen_height);
+        let dialog_width = self.screen.scale_x(DIALOG_WIDTH as i32) as u32;
+        let dialog_height = self.screen.scale_y(DIALOG_HEIGHT as i32) as u32;
+        Rect::from_center(screen_rect.center(), dialog_width, dialog_height)
+    }
+
+    /// Converts a distance measured on the art into one on screen.
+    fn scale_asset(&self, distance: u32) -> u32 {
+        let (asset_width, _) = ASSET_DIMENSIONS;
+        let dialog_width = self.dialog_rect().width();
+        (distance as f32 * dialog_width as f32 / asset_width as f32) as u32
+    }
+
+    /// Selects the building above or below the selected one in its category's list.
+    fn adjacent_building_action(game: &GameState, delta: i32) -> Option<GameAction> {
+        if let BuyBuildingScreenState::Visible {
+            selected_building,
+            selected_category,
+            ..
+        } = game.buy_building_screen
+        {
+            let buildings = selected_category.buildings();
+            let idx = buildings.iter().position(|&b| b == selected_building)? as i32;
+            let building = *buildings.get((idx + delta).max(0) as usize)?;
+            Some(GameAction::SelectBuilding { building })
+        } else {
+            None
         }
     }
 }
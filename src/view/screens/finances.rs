@@ -0,0 +1,179 @@
+use crate::action::GameAction;
+use crate::state::company::{
+    format_money, LedgerTotals, Money, ANNUAL_INTEREST_PERCENT, BANKRUPTCY_DAYS, LEDGER_CATEGORIES,
+};
+use crate::state::game::GameState;
+use crate::systems::finance;
+use crate::view::text::DynamicText;
+use crate::view::{
+    KeyboardKey, PlayerInteraction, ScreenState, DIALOG_HEIGHT, DIALOG_WIDTH, TEXT_HEIGHT,
+};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::rc::Rc;
+
+const COLOR_BACKGROUND: (u8, u8, u8) = (82, 82, 82);
+const COLOR_DIALOG: (u8, u8, u8) = (132, 132, 123);
+const LINE_SPACING: u32 = 4;
+const MARGIN: u32 = 16;
+/// Left edge of the row labels and of the this month, last month, this year and last year
+/// columns, relative to the dialog.
+const COLUMNS: [u32; 5] = [MARGIN, 170, 290, 410, 530];
+/// Lines above the table: the title, the controls, a gap, the cash, the loan, the interest, a gap
+/// and the column headings.
+const HEADER_LINES: u32 = 8;
+
+pub struct FinancesScreen {
+    screen: ScreenState,
+    dynamic_text: Rc<DynamicText>,
+}
+
+impl FinancesScreen {
+    pub fn new(dynamic_text: Rc<DynamicText>, screen: ScreenState) -> Self {
+        Self {
+            dynamic_text,
+            screen,
+        }
+    }
+
+    pub fn map_player_interaction(&self, player_action: PlayerInteraction) -> Option<GameAction> {
+        match player_action {
+            PlayerInteraction::KeyPress {
+                key: KeyboardKey::Escape,
+            } => Some(GameAction::CloseFinancesScreen),
+            PlayerInteraction::KeyPress {
+                key: KeyboardKey::B,
+            } => Some(GameAction::Borrow),
+            PlayerInteraction::KeyPress {
+                key: KeyboardKey::R,
+            } => Some(GameAction::RepayLoan),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, canvas: &mut Canvas<Window>, game: &GameState) -> Result<(), String> {
+        let (screen_width, screen_height) = self.screen.size();
+
+        canvas.set_draw_color(Color::from(COLOR_BACKGROUND));
+        let screen_rect = Rect::new(0, 0, screen_width, screen_height);
+        canvas.fill_rect(screen_rect)?;
+
+        let dialog_width = self.screen.scale_x(DIALOG_WIDTH as i32) as u32;
+        let dialog_height = self.screen.scale_y(DIALOG_HEIGHT as i32) as u32;
+        let dialog_rect = Rect::from_center(screen_rect.center(), dialog_width, dialog_height);
+        canvas.set_draw_color(Color::from(COLOR_DIALOG));
+        canvas.fill_rect(dialog_rect)?;
+
+        let margin = self.screen.scale_y(MARGIN as i32);
+        let line_spacing = self.screen.scale_y(LINE_SPACING as i32);
+        let line_height = self.screen.scale_y(TEXT_HEIGHT as i32) + line_spacing;
+        let left = dialog_rect.left();
+        let line_top = |line: u32| dialog_rect.top() + margin + line_height * line as i32;
+
+        let company = &game.company;
+        let ledger = &company.ledger;
+        self.print_row(canvas, &["Finances"], left, line_top(0))?;
+        self.print_row(
+            canvas,
+            &["B: borrow   R: repay   Esc: close"],
+            left,
+            line_top(1),
+        )?;
+        let debt_warning = if company.days_in_debt > 0 {
+            format!(
+                "Bankrupt in {} days",
+                BANKRUPTCY_DAYS.saturating_sub(company.days_in_debt)
+            )
+        } else {
+            String::new()
+        };
+        self.print_row(
+            canvas,
+            &["Cash", &format_money(company.cash), &debt_warning],
+            left,
+            line_top(3),
+        )?;
+        self.print_row(
+            canvas,
+            &[
+                "Loan",
+                &format_money(company.loan),
+                "Limit",
+                &format_money(finance::loan_limit(game)),
+            ],
+            left,
+            line_top(4),
+        )?;
+        self.print_row(
+            canvas,
+            &[
+                "Interest",
+                &format!("{}% a year", ANNUAL_INTEREST_PERCENT),
+                "Monthly",
+                &format_money(finance::monthly_interest(company.loan)),
+            ],
+            left,
+            line_top(5),
+        )?;
+        self.print_row(
+            canvas,
+            &["", "This month", "Last month", "This year", "Last year"],
+            left,
+            line_top(7),
+        )?;
+
+        let periods = [
+            Some(ledger.this_month),
+            ledger.past_months.front().map(|&(_, _, totals)| totals),
+            Some(ledger.this_year),
+            ledger.past_years.front().map(|&(_, totals)| totals),
+        ];
+        let amounts = |label: &str, amount: &dyn Fn(&LedgerTotals) -> Money| {
+            let mut row = vec![String::from(label)];
+            row.extend(periods.iter().map(|totals| match totals {
+                Some(totals) => format_money(amount(totals)),
+                None => String::from("-"),
+            }));
+            row
+        };
+
+        let mut rows = vec![vec![String::from("Income")]];
+        for &category in LEDGER_CATEGORIES.iter() {
+            let label = format!("  {}", category.name());
+            rows.push(amounts(&label, &|totals| totals.income(category)));
+        }
+        rows.push(amounts("Total income", &|totals| totals.total_income()));
+        rows.push(vec![String::from("Expenses")]);
+        for &category in LEDGER_CATEGORIES.iter() {
+            let label = format!("  {}", category.name());
+            rows.push(amounts(&label, &|totals| totals.expenses(category)));
+        }
+        rows.push(amounts("Total expenses", &|totals| totals.total_expenses()));
+        rows.push(amounts("Profit", &|totals| totals.profit()));
+
+        for (idx, row) in rows.iter().enumerate() {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            self.print_row(canvas, &cells, left, line_top(HEADER_LINES + idx as u32))?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints each cell at the start of its column, with the columns measured from `left`.
+    fn print_row(
+        &self,
+        canvas: &mut Canvas<Window>,
+        cells: &[&str],
+        left: i32,
+        top: i32,
+    ) -> Result<(), String> {
+        for (cell, &column) in cells.iter().zip(COLUMNS.iter()) {
+            let column_left = left + self.screen.scale_x(column as i32);
+            self.dynamic_text
+                .print(canvas, cell, Point::new(column_left, top), false)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+use crate::action::GameAction;
+use crate::state::company::format_money;
+use crate::storage::slots::SlotSummary;
+use crate::view::text::DynamicText;
+use crate::view::{
+    KeyboardKey, PlayerInteraction, ScreenState, DIALOG_HEIGHT, DIALOG_WIDTH, TEXT_HEIGHT,
+};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::rc::Rc;
+
+const COLOR_BACKGROUND: (u8, u8, u8) = (82, 82, 82);
+const COLOR_DIALOG: (u8, u8, u8) = (132, 132, 123);
+const COLOR_HIGHLIGHT: (u8, u8, u8) = (255, 255, 255);
+const LINE_SPACING: u32 = 4;
+const MARGIN: u32 = 16;
+/// Left edge of the slot, date, cash and map size columns, relative to the dialog.
+const COLUMNS: [u32; 4] = [MARGIN, 260, 380, 540];
+/// Lines above the list of slots: the title, the controls, the name being typed or a gap, and the
+/// column headings.
+const HEADER_LINES: u32 = 4;
+
+pub struct LoadScreen {
+    screen: ScreenState,
+    dynamic_text: Rc<DynamicText>,
+}
+
+impl LoadScreen {
+    pub fn new(dynamic_text: Rc<DynamicText>, screen: ScreenState) -> Self {
+        Self {
+            dynamic_text,
+            screen,
+        }
+    }
+
+    /// While a slot is being named, typing goes into the name rather than choosing what to do.
+    pub fn map_player_interaction(
+        &self,
+        naming: bool,
+        player_action: PlayerInteraction,
+    ) -> Option<GameAction> {
+        if naming {
+            return match player_action {
+                PlayerInteraction::TextInput { character } => {
+                    Some(GameAction::TypeSlotName { character })
+                }
+                PlayerInteraction::KeyPress { key } => match key {
+                    KeyboardKey::Backspace => Some(GameAction::EraseSlotName),
+                    KeyboardKey::Enter => Some(GameAction::SaveGame),
+                    KeyboardKey::Escape => Some(GameAction::CancelSlotName),
+                    _ => None,
+                },
+                _ => None,
+            };
+        }
+
+        match player_action {
+            PlayerInteraction::KeyPress { key } => match key {
+                KeyboardKey::Up => Some(GameAction::SelectSlot { delta: -1 }),
+                KeyboardKey::Down => Some(GameAction::SelectSlot { delta: 1 }),
+                KeyboardKey::Enter => Some(GameAction::LoadGame),
+                KeyboardKey::S => Some(GameAction::SaveGame),
+                KeyboardKey::N => Some(GameAction::NameSlot),
+                KeyboardKey::Escape => Some(GameAction::CloseLoadScreen),
+                _ => None,
+            },
+            PlayerInteraction::WindowScroll { delta, .. } => Some(GameAction::SelectSlot {
+                delta: -delta.signum() as i8,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn render(
+        &self,
+        canvas: &mut Canvas<Window>,
+        slots: &[SlotSummary],
+        selected: usize,
+        naming: Option<&str>,
+    ) -> Result<(), String> {
+        let (screen_width, screen_height) = self.screen.size();
+
+        canvas.set_draw_color(Color::from(COLOR_BACKGROUND));
+        let screen_rect = Rect::new(0, 0, screen_width, screen_height);
+        canvas.fill_rect(screen_rect)?;
+
+        let dialog_width = self.screen.scale_x(DIALOG_WIDTH as i32) as u32;
+        let dialog_height = self.screen.scale_y(DIALOG_HEIGHT as i32) as u32;
+        let dialog_rect = Rect::from_center(screen_rect.center(), dialog_width, dialog_height);
+        canvas.set_draw_color(Color::from(COLOR_DIALOG));
+        canvas.fill_rect(dialog_rect)?;
+
+        let margin = self.screen.scale_y(MARGIN as i32);
+        let line_spacing = self.screen.scale_y(LINE_SPACING as i32);
+        let line_height = self.screen.scale_y(TEXT_HEIGHT as i32) + line_spacing;
+        let left = dialog_rect.left();
+        let line_top = |line: u32| dialog_rect.top() + margin + line_height * line as i32;
+
+        self.print_row(canvas, &["Saved Games"], left, line_top(0), false)?;
+        match naming {
+            Some(name) => {
+                self.print_row(
+                    canvas,
+                    &["Type a name for the slot   Enter: save   Esc: cancel"],
+                    left,
+                    line_top(1),
+                    false,
+                )?;
+                self.print_row(
+                    canvas,
+                    &[&format!("Save as: {}_", name)],
+                    left,
+                    line_top(2),
+                    false,
+                )?;
+            }
+            None => self.print_row(
+                canvas,
+                &["Up/Down: choose  Enter: load  S: save  N: save as a new slot  Esc: close"],
+                left,
+                line_top(1),
+                false,
+            )?,
+        }
+        self.print_row(
+            canvas,
+            &["Slot", "Date", "Cash", "Map"],
+            left,
+            line_top(3),
+            false,
+        )?;
+
+        if slots.is_empty() {
+            return self.print_row(
+                canvas,
+                &["No saved games"],
+                left,
+                line_top(HEADER_LINES),
+                false,
+            );
+        }
+
+        // Scroll the list so the selected slot is always on screen.
+        let visible_rows =
+            ((dialog_height as i32 - margin * 2) / line_height) as usize - HEADER_LINES as usize;
+        let first = (selected + 1).saturating_sub(visible_rows);
+        for (row, slot) in slots.iter().enumerate().skip(first).take(visible_rows) {
+            let top = line_top(HEADER_LINES + (row - first) as u32);
+            let highlight = row == selected;
+            if highlight {
+                canvas.set_draw_color(Color::from(COLOR_HIGHLIGHT));
+                canvas.fill_rect(Rect::new(
+                    dialog_rect.left() + margin / 2,
+                    top - line_spacing / 2,
+                    dialog_width - margin as u32,
+                    line_height as u32,
+                ))?;
+            }
+
+            match &slot.metadata {
+                Ok(metadata) => self.print_row(
+                    canvas,
+                    &[
+                        &slot.label,
+                        &metadata.date.to_string(),
+                        &format_money(metadata.cash),
+                        &format!("{}x{}", metadata.width, metadata.height),
+                    ],
+                    left,
+                    top,
+                    highlight,
+                )?,
+                Err(e) => self.print_row(canvas, &[&slot.label, e], left, top, highlight)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints each cell at the start of its column, with the columns measured from `left`.
+    fn print_row(
+        &self,
+        canvas: &mut Canvas<Window>,
+        cells: &[&str],
+        left: i32,
+        top: i32,
+        invert: bool,
+    ) -> Result<(), String> {
+        for (cell, &column) in cells.iter().zip(COLUMNS.iter()) {
+            let column_left = left + self.screen.scale_x(column as i32);
+            self.dynamic_text
+                .print(canvas, cell, Point::new(column_left, top), invert)?;
+        }
+        Ok(())
+    }
+}
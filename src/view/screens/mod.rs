@@ -1 +1,4 @@
+pub mod bankruptcy;
 pub mod building;
+pub mod finances;
+pub mod load;
@@ -1,26 +1,35 @@
+use super::text::DynamicText;
 use super::{ScreenState, SIDEBAR_WIDTH, TEXT_HEIGHT};
 use crate::action::GameAction;
-use crate::state::game::GameState;
+use crate::state::company::format_money;
+use crate::state::game::{GameState, PlayerMode};
 use crate::state::menu::sidebar::SidebarMenu;
 use sdl2::image::LoadTexture;
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
+use sdl2::rect::{Point, Rect};
 use sdl2::render::{Canvas, Texture, TextureCreator};
 use sdl2::video::Window;
+use std::rc::Rc;
 
 const COLOR_SIDEBAR: (u8, u8, u8) = (132, 132, 123);
 const COLOR_DEPRESSED_BUTTON_OUTLINE: (u8, u8, u8) = (255, 0, 0);
 const COLOR_DEPRESSED_BUTTON_BACKGROUND: (u8, u8, u8, u8) = (255, 255, 255, 100);
 
 const BUTTONS_PER_ROW: u32 = 5;
+const HEADER_MARGIN: i32 = 4;
 
 pub struct Sidebar {
     button_textures: [Texture; 15],
+    dynamic_text: Rc<DynamicText>,
     screen: ScreenState,
 }
 
 impl Sidebar {
-    pub fn new<T>(texture_creator: &TextureCreator<T>, screen: ScreenState) -> Self {
+    pub fn new<T>(
+        texture_creator: &TextureCreator<T>,
+        dynamic_text: Rc<DynamicText>,
+        screen: ScreenState,
+    ) -> Self {
         Self {
             button_textures: [
                 texture_creator.load_texture("art/close_128.png").unwrap(),
@@ -41,6 +50,7 @@ impl Sidebar {
                 texture_creator.load_texture("art/dozer_128.png").unwrap(),
                 texture_creator.load_texture("art/point_128.png").unwrap(),
             ],
+            dynamic_text,
             screen,
         }
     }
@@ -53,6 +63,8 @@ impl Sidebar {
 
     pub fn left_click_action(&self, x: i32, y: i32) -> Option<GameAction> {
         match self.button_under_cursor(x, y) {
+            // Saving is done as soon as the button is pressed, with no menu to open.
+            Some(SidebarMenu::Save) => Some(GameAction::SaveGame),
             Some(menu) => Some(GameAction::OpenMenu { menu }),
             None => None,
         }
@@ -65,6 +77,8 @@ impl Sidebar {
         canvas.set_draw_color(Color::from(COLOR_SIDEBAR));
         canvas.fill_rect(sidebar_rect)?;
 
+        self.draw_header(canvas, game)?;
+
         // Draw buttons
         self.draw_button(canvas, game, SidebarMenu::Close)?;
         self.draw_button(canvas, game, SidebarMenu::Save)?;
@@ -84,9 +98,86 @@ impl Sidebar {
         self.draw_button(canvas, game, SidebarMenu::Demolish)?;
         self.draw_button(canvas, game, SidebarMenu::Point)?;
 
+        self.draw_mode(canvas, game)?;
+        self.draw_cost_preview(canvas, game)?;
+
+        Ok(())
+    }
+
+    /// Shows the date and how fast the game is running in the lines above the buttons.
+    fn draw_header(&self, canvas: &mut Canvas<Window>, game: &GameState) -> Result<(), String> {
+        let speed = if game.company.bankrupt {
+            String::from("Bankrupt")
+        } else if game.is_paused() {
+            String::from("Paused")
+        } else {
+            format!("Speed: {}", game.speed)
+        };
+        let text_height = self.screen.scale_y(TEXT_HEIGHT as i32);
+        let left = self.screen.scale_x(HEADER_MARGIN);
+        for (line, content) in [game.date.to_string(), speed].iter().enumerate() {
+            let top = text_height / 2 + line as i32 * text_height;
+            self.dynamic_text
+                .print(canvas, content, Point::new(left, top), true)?;
+        }
         Ok(())
     }
 
+    /// Names the tool the player is using, with the brush radius while terraforming, in the first
+    /// line below the buttons.
+    fn draw_mode(&self, canvas: &mut Canvas<Window>, game: &GameState) -> Result<(), String> {
+        let mode = match game.player_mode {
+            PlayerMode::Focus => String::from("Navigate"),
+            PlayerMode::Demolish => String::from("Demolish"),
+            PlayerMode::RaiseLower { brush } => format!("Terraform, radius {}", brush.radius),
+            PlayerMode::PlaceStructure { .. } => String::from("Build"),
+            PlayerMode::Flatten { .. } => String::from("Level"),
+        };
+        let left = self.screen.scale_x(HEADER_MARGIN);
+        self.dynamic_text.print(
+            canvas,
+            &mode,
+            Point::new(left, self.below_buttons_top()),
+            true,
+        )
+    }
+
+    /// Lists what the action the player is lining up would cost, below the mode.
+    fn draw_cost_preview(
+        &self,
+        canvas: &mut Canvas<Window>,
+        game: &GameState,
+    ) -> Result<(), String> {
+        let text_height = self.screen.scale_y(TEXT_HEIGHT as i32);
+        let top = self.below_buttons_top() + text_height;
+        let left = self.screen.scale_x(HEADER_MARGIN);
+
+        let mut lines: Vec<String> = game
+            .cost_preview
+            .lines
+            .iter()
+            .map(|(label, cost)| format!("{}: {}", label, format_money(cost.abs())))
+            .collect();
+        if game.cost_preview.unaffordable {
+            lines.push(String::from("Not enough cash"));
+        }
+        for (line, content) in lines.iter().enumerate() {
+            let top = top + line as i32 * text_height;
+            self.dynamic_text
+                .print(canvas, content, Point::new(left, top), true)?;
+        }
+        Ok(())
+    }
+
+    /// The top of the first line of text below the button grid.
+    fn below_buttons_top(&self) -> i32 {
+        let (width, _) = self.size();
+        let button_height = width / BUTTONS_PER_ROW;
+        let text_height = self.screen.scale_y(TEXT_HEIGHT as i32);
+        let button_grid_offset_y = text_height * 3;
+        button_grid_offset_y + button_height as i32 * 3 + text_height / 2
+    }
+
     fn button_under_cursor(&self, x: i32, y: i32) -> Option<SidebarMenu> {
         let (width, _) = self.size();
         let button_width = width / BUTTONS_PER_ROW;
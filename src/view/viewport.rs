@@ -6,6 +6,7 @@ use sdl2::rect;
 use sdl2::render::Canvas;
 use sdl2::video::Window;
 use std::f32::consts::PI;
+use std::ops::Range;
 use std::time::Instant;
 
 use super::{ScreenState, COLOR_BLACK, COLOR_WHITE, SIDEBAR_WIDTH};
@@ -17,6 +18,9 @@ const HEIGHT_UNIT_OFFSET: u32 = 10;
 const GRID_SCALE: f32 = 30.0;
 
 const COLOR_HIGHLIGHT_BLOCK: (u8, u8, u8, u8) = (255, 255, 255, 150);
+const COLOR_INVALID_BLOCK: (u8, u8, u8, u8) = (255, 0, 0, 150);
+const COLOR_PREVIEW_RAISE: (u8, u8, u8) = (255, 220, 0);
+const COLOR_PREVIEW_LOWER: (u8, u8, u8) = (0, 220, 255);
 const COLOR_WATER: (u8, u8, u8) = (53, 117, 189);
 const COLOR_LAND: (u8, u8, u8) = (0, 200, 0);
 const COLOR_FOREST: (u8, u8, u8) = (47, 99, 67);
@@ -121,14 +125,30 @@ impl Viewport {
     pub fn left_click_action(&self, game: &GameState) -> Option<GameAction> {
         match &game.player_mode {
             PlayerMode::Focus => Some(GameAction::Focus),
+            PlayerMode::Demolish => Some(GameAction::Demolish),
             PlayerMode::RaiseLower { .. } => Some(GameAction::RaiseTerrain),
             PlayerMode::PlaceStructure { .. } => Some(GameAction::PlaceStructure),
+            PlayerMode::Flatten { .. } => Some(GameAction::Flatten),
         }
     }
 
     pub fn right_click_action(&self, game: &GameState) -> Option<GameAction> {
         match &game.player_mode {
             PlayerMode::RaiseLower { .. } => Some(GameAction::LowerTerrain),
+            PlayerMode::Flatten { .. } => Some(GameAction::CancelFlatten),
+            _ => None,
+        }
+    }
+
+    pub fn scroll_action(&self, game: &GameState, delta: i32) -> Option<GameAction> {
+        self.adjust_action(game, delta.signum() as i8)
+    }
+
+    /// Grows or shrinks whatever setting the current tool has, such as the brush radius.
+    pub fn adjust_action(&self, game: &GameState, delta: i8) -> Option<GameAction> {
+        match &game.player_mode {
+            PlayerMode::RaiseLower { .. } => Some(GameAction::ResizeBrush { delta }),
+            PlayerMode::Flatten { .. } => Some(GameAction::AdjustFlattenLevel { delta }),
             _ => None,
         }
     }
@@ -205,19 +225,14 @@ impl Viewport {
         let draw_begin = Instant::now();
         let board_width = game.board.width();
         let board_height = game.board.height();
-        // Draw the ground layer.
-        for y in 0..(board_height + 1) {
-            // Skip any points outside viewport bounding box.
-            if (y as f32) < min_y || (y as f32) > max_y {
-                continue;
-            }
-
-            for x in 0..(board_width + 1) {
-                // Skip any points outside viewport bounding box.
-                if (x as f32) < min_x || (x as f32) > max_x {
-                    continue;
-                }
+        // Only visit the vertices inside the viewport bounding box. On large boards this is a
+        // small fraction of the whole board.
+        let visible_x = visible_range(min_x, max_x, board_width);
+        let visible_y = visible_range(min_y, max_y, board_height);
 
+        // Draw the ground layer.
+        for y in visible_y.clone() {
+            for x in visible_x.clone() {
                 if x < board_width && y < board_height {
                     let tile_color = match game.board.block_land_type(x, y) {
                         LandType::Water => Color::from(COLOR_WATER),
@@ -240,15 +255,9 @@ impl Viewport {
         }
 
         // Draw any structures.
-        for y in 0..board_height {
-            // Skip any blocks outside viewport bounding box.
-            if (y as f32) < min_y || (y as f32) > max_y {
-                continue;
-            }
-
-            for x in 0..board_width {
-                // Skip any blocks outside viewport bounding box.
-                if (x as f32) < min_x || (x as f32) > max_x {
+        for y in visible_y.clone() {
+            for x in visible_x.clone() {
+                if x >= board_width || y >= board_height {
                     continue;
                 }
 
@@ -264,6 +273,21 @@ impl Viewport {
             match game.selection_mode() {
                 SelectionMode::None => {}
                 SelectionMode::Blocks { w, h } => {
+                    // Show at a glance whether the structure would fit here.
+                    let color = match game.player_mode {
+                        PlayerMode::PlaceStructure {
+                            structure,
+                            orientation,
+                        } if game
+                            .board
+                            .can_place(structure, orientation, *block)
+                            .is_err() =>
+                        {
+                            Color::from(COLOR_INVALID_BLOCK)
+                        }
+                        _ => Color::from(COLOR_HIGHLIGHT_BLOCK),
+                    };
+
                     // Highlight all blocks in (w X h) with current block at the top.
                     // w is positive along x-axis; h is positive along y-axis.
                     for y in 0..h {
@@ -274,17 +298,46 @@ impl Viewport {
                                 &game,
                                 block.x as i32 + x as i32,
                                 block.y as i32 + y as i32,
-                                Color::from(COLOR_HIGHLIGHT_BLOCK),
+                                color,
                             )?;
                         }
                     }
                 }
-                SelectionMode::Vertex { radius } => {
-                    for y in (block.y as i32 - radius as i32)..(block.y as i32 + radius as i32 + 1)
-                    {
-                        for x in
-                            (block.x as i32 - radius as i32)..(block.x as i32 + radius as i32 + 1)
-                        {
+                SelectionMode::Area { anchor } => {
+                    let color = match game.terrain_preview {
+                        Some(Err(_)) => Color::from(COLOR_INVALID_BLOCK),
+                        _ => Color::from(COLOR_HIGHLIGHT_BLOCK),
+                    };
+
+                    let (origin, w, h) = anchor.span(*block);
+                    for y in origin.y..origin.y + h {
+                        for x in origin.x..origin.x + w {
+                            fill_block(canvas, &self, &game, x as i32, y as i32, color)?;
+                        }
+                    }
+
+                    // Mark every vertex the levelling would move.
+                    if let Some(Ok(plan)) = &game.terrain_preview {
+                        for change in plan.changes().iter() {
+                            let color = if change.to > change.from {
+                                Color::from(COLOR_PREVIEW_RAISE)
+                            } else {
+                                Color::from(COLOR_PREVIEW_LOWER)
+                            };
+                            let Vertex { x, y } = change.vertex;
+                            draw_vertex(canvas, &self, &game, x as i32, y as i32, color)?;
+                        }
+                    }
+                }
+                SelectionMode::Vertex { brush } => {
+                    let radius = brush.radius as i32;
+                    for dy in -radius..(radius + 1) {
+                        for dx in -radius..(radius + 1) {
+                            if brush.distance(dx, dy).is_none() {
+                                continue;
+                            }
+                            let x = block.x as i32 + dx;
+                            let y = block.y as i32 + dy;
                             draw_vertex(canvas, &self, &game, x, y, Color::from(COLOR_WHITE))?;
                         }
                     }
@@ -450,6 +503,17 @@ impl From<&WorldPoint> for ScreenPoint {
     }
 }
 
+/// The board coordinates from `min` to `max` inclusive, clipped to the `0..=limit` range.
+fn visible_range(min: f32, max: f32, limit: u32) -> Range<u32> {
+    if max < 0.0 {
+        return 0..0;
+    }
+
+    let first = min.max(0.0).ceil() as u32;
+    let last = (max.floor() as u32).min(limit);
+    first..(last + 1)
+}
+
 fn draw_vertex(
     canvas: &mut Canvas<Window>,
     viewport: &Viewport,
@@ -0,0 +1,242 @@
+use crate::action::GameAction;
+use crate::state::game::GameState;
+use crate::storage;
+use crate::storage::recording::Recorder;
+use crate::storage::save::SaveError;
+use crate::storage::slots::AutosaveSettings;
+use crate::systems;
+use crate::systems::replay::Replay;
+use crate::view::{Interface, KeyboardKey, PlayerInteraction, ScreenState};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::MouseButton;
+use std::fs::File;
+use std::io::BufWriter;
+use std::ops::{Add, Sub};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const UPDATES_PER_SECOND: u32 = 120;
+const MAX_FRAMES_PER_SECOND: u32 = 60;
+/// The most simulation steps run between two frames when the game falls behind.
+const MAX_UPDATES_PER_FRAME: u32 = 10;
+const HEIGHTMAP_EXPORT_PATH: &str = "heightmap.png";
+const MAP_EXPORT_PATH: &str = "map.txt";
+
+/// Opens the game window and plays until the player quits. A replay, when given, drives the game
+/// until it finishes, and a recorder, when given, records everything the player does.
+pub fn run_window(
+    mut game: GameState,
+    mut replay: Option<Replay>,
+    mut recorder: Option<Recorder<BufWriter<File>>>,
+    autosave: AutosaveSettings,
+) -> Result<(), String> {
+    let sdl_ctx = sdl2::init()?;
+    let vid_subsystem = sdl_ctx.video()?;
+
+    let window = vid_subsystem
+        .window("Titan", 640, 480)
+        .position_centered()
+        .allow_highdpi()
+        .fullscreen_desktop()
+        .opengl()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let (window_width, window_height) = window.size();
+    let (drawable_x, drawable_y) = window.drawable_size();
+
+    let mut canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+    let texture_creator = canvas.texture_creator();
+
+    let screen = ScreenState::new(window_width, window_height, drawable_x, drawable_y);
+    let interface = Interface::new(texture_creator, screen);
+
+    let mut event_pump = sdl_ctx.event_pump()?;
+    let update_interval = Duration::new(0, 1_000_000_000 / UPDATES_PER_SECOND);
+    let mut next_update = Instant::now();
+    let mut last_frame = Instant::now();
+    let mut frame_count: u64 = 0;
+    let mut cursor = (0, 0);
+    let mut tick: u64 = 0;
+    let mut last_autosave = Instant::now();
+    'running: loop {
+        let mut player_interactions = Vec::new();
+
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    keymod,
+                    ..
+                } => {
+                    let ctrl = keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD);
+                    let shift = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+                    let key = match keycode {
+                        Keycode::Z if ctrl && shift => Some(KeyboardKey::Redo),
+                        Keycode::Z if ctrl => Some(KeyboardKey::Undo),
+                        Keycode::Y if ctrl => Some(KeyboardKey::Redo),
+                        Keycode::Space => Some(KeyboardKey::Space),
+                        Keycode::Return => Some(KeyboardKey::Enter),
+                        Keycode::Escape => Some(KeyboardKey::Escape),
+                        Keycode::Backspace => Some(KeyboardKey::Backspace),
+                        Keycode::Up => Some(KeyboardKey::Up),
+                        Keycode::Down => Some(KeyboardKey::Down),
+                        Keycode::LeftBracket => Some(KeyboardKey::LeftBracket),
+                        Keycode::RightBracket => Some(KeyboardKey::RightBracket),
+                        Keycode::B => Some(KeyboardKey::B),
+                        Keycode::E => Some(KeyboardKey::E),
+                        Keycode::F => Some(KeyboardKey::F),
+                        Keycode::L => Some(KeyboardKey::L),
+                        Keycode::M => Some(KeyboardKey::M),
+                        Keycode::N => Some(KeyboardKey::N),
+                        Keycode::O => Some(KeyboardKey::O),
+                        Keycode::P => Some(KeyboardKey::P),
+                        Keycode::R => Some(KeyboardKey::R),
+                        Keycode::S => Some(KeyboardKey::S),
+                        Keycode::T => Some(KeyboardKey::T),
+                        Keycode::X => Some(KeyboardKey::X),
+                        Keycode::Num1 => Some(KeyboardKey::Num1),
+                        Keycode::Num2 => Some(KeyboardKey::Num2),
+                        Keycode::Num3 => Some(KeyboardKey::Num3),
+                        Keycode::Num4 => Some(KeyboardKey::Num4),
+                        _ => None,
+                    };
+                    if let Some(key) = key {
+                        player_interactions.push(PlayerInteraction::KeyPress { key });
+                    }
+                }
+                Event::TextInput { text, .. } => {
+                    for character in text.chars() {
+                        player_interactions.push(PlayerInteraction::TextInput { character });
+                    }
+                }
+                Event::MouseMotion { x, y, .. } => {
+                    let x = screen.scale_x(x) as i32;
+                    let y = screen.scale_y(y) as i32;
+                    cursor = (x, y);
+                    let player_action = PlayerInteraction::CursorMove { x, y };
+                    player_interactions.push(player_action);
+                }
+                Event::MouseButtonDown {
+                    x, y, mouse_btn, ..
+                } => {
+                    let x = screen.scale_x(x) as i32;
+                    let y = screen.scale_y(y) as i32;
+                    let player_action = match mouse_btn {
+                        MouseButton::Left => Some(PlayerInteraction::WindowLeftClick { x, y }),
+                        MouseButton::Right => Some(PlayerInteraction::WindowRightClick { x, y }),
+                        _ => None,
+                    };
+                    if let Some(player_action) = player_action {
+                        player_interactions.push(player_action);
+                    }
+                }
+                Event::MouseWheel { y: delta, .. } => {
+                    let (x, y) = cursor;
+                    let player_action = PlayerInteraction::WindowScroll { x, y, delta };
+                    player_interactions.push(player_action);
+                }
+                _ => {}
+            }
+        }
+
+        // Resolve all actions.
+        for &player_interaction in player_interactions.iter() {
+            let action = match interface.map_player_interaction(&game, player_interaction) {
+                Some(action) => action,
+                None => continue,
+            };
+            // While a replay is playing, it drives the game and the player can only quit.
+            if replay.is_some() && action != GameAction::Quit {
+                continue;
+            }
+
+            if let Some(writer) = recorder.as_mut() {
+                let result = writer.record_action(tick, action);
+                stop_recording_on_error(&mut recorder, result);
+            }
+
+            match action {
+                GameAction::Quit => break 'running,
+                GameAction::ExportHeightmap => {
+                    match storage::heightmap::save_heightmap(&game.board, HEIGHTMAP_EXPORT_PATH) {
+                        Ok(()) => println!("Heightmap exported to {}.", HEIGHTMAP_EXPORT_PATH),
+                        Err(e) => println!("Heightmap export failed: {}", e),
+                    }
+                }
+                GameAction::ExportMap => {
+                    match storage::text_map::save_map(&game.board, MAP_EXPORT_PATH) {
+                        Ok(()) => println!("Map exported to {}.", MAP_EXPORT_PATH),
+                        Err(e) => println!("Map export failed: {}", e),
+                    }
+                }
+                _ => systems::apply_action(&mut game, action),
+            }
+
+            // A loaded game can't be rebuilt from the actions, so the recording keeps all of it.
+            if action == GameAction::LoadGame {
+                if let Some(writer) = recorder.as_mut() {
+                    let result = writer.record_snapshot(tick, &game);
+                    stop_recording_on_error(&mut recorder, result);
+                }
+            }
+        }
+
+        if let Some(interval) = autosave.interval {
+            if last_autosave.elapsed() >= interval {
+                systems::save::apply_autosave(&game, &autosave);
+                last_autosave = Instant::now();
+            }
+        }
+
+        // The simulation steps at a fixed rate however often frames are drawn, catching up on
+        // steps missed while a frame took too long, up to a limit.
+        let mut steps = 0;
+        while next_update <= Instant::now() {
+            if steps == MAX_UPDATES_PER_FRAME {
+                next_update = Instant::now();
+                break;
+            }
+            if let Some(playing) = replay.as_mut() {
+                playing.apply_tick(&mut game, tick);
+                if playing.is_finished() {
+                    println!("Replay finished.");
+                    replay = None;
+                }
+            }
+            systems::clock::apply_update(&mut game);
+            tick += 1;
+            steps += 1;
+            next_update = next_update.add(update_interval);
+        }
+
+        if last_frame.elapsed() > Duration::new(0, 1_000_000_000 / MAX_FRAMES_PER_SECOND) {
+            let render_start = Instant::now();
+            interface.render(&mut canvas, &game)?;
+            frame_count += 1;
+            // println!("Frame {}: {:?}", frame_count, render_start.elapsed());
+            last_frame = Instant::now();
+        }
+
+        let now = Instant::now();
+        if next_update > now {
+            let delay = next_update.sub(now);
+            thread::sleep(delay);
+        }
+    }
+
+    Ok(())
+}
+
+/// Stops recording if the recording can't be written to, rather than interrupting the game.
+fn stop_recording_on_error(
+    recorder: &mut Option<Recorder<BufWriter<File>>>,
+    result: Result<(), SaveError>,
+) {
+    if let Err(e) = result {
+        println!("Recording stopped: {}", e);
+        *recorder = None;
+    }
+}